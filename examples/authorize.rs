@@ -0,0 +1,25 @@
+use ecobee::{request_pin, Scope};
+
+fn main() {
+    let api_key = std::env::var("ECOBEE_KEY").expect("ECOBEE_KEY must be set to authorize");
+    let scope = match std::env::args().nth(1).as_deref() {
+        None | Some("smartWrite") => Scope::smartWrite,
+        Some("smartRead") => Scope::smartRead,
+        Some("ems") => Scope::ems,
+        Some(other) => panic!(
+            "Unknown scope {}, expected smartRead, smartWrite or ems",
+            other
+        ),
+    };
+    let mut pin = request_pin(&api_key, scope).expect("Failed to request a PIN");
+    println!(
+        "Enter PIN {} under My Apps on ecobee.com before {}",
+        pin.ecobeePin,
+        pin.expires_at().format("%H:%M:%S UTC")
+    );
+    let bee = pin
+        .wait_for_authorization(&api_key)
+        .expect("The PIN was not authorized");
+    println!("export ECOBEE_AUTH={}", bee.access_token().expose());
+    println!("export ECOBEE_REFRESH={}", bee.refresh_token().expose());
+}
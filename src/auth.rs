@@ -0,0 +1,501 @@
+use crate::{parse_body, Ecobee, EcobeeError, Secret};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use std::thread::sleep;
+use std::time::Instant;
+
+const AUTHORIZE_URL: &str = "https://api.ecobee.com/authorize";
+const TOKEN_URL: &str = "https://api.ecobee.com/token";
+
+/// What an application is allowed to do with the thermostats it was granted, from least to most
+/// permissive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Scope {
+    smartRead,
+    smartWrite,
+    ems,
+}
+
+impl Scope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Scope::smartRead => "smartRead",
+            Scope::smartWrite => "smartWrite",
+            Scope::ems => "ems",
+        }
+    }
+
+    pub fn allows_write(&self) -> bool {
+        *self != Scope::smartRead
+    }
+
+    /// Pick the broadest ecobee scope out of the `scope` string of a token response, which may
+    /// list several space or comma separated scopes.
+    pub fn from_granted(granted: &str) -> Option<Scope> {
+        granted
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter_map(|s| s.parse().ok())
+            .max()
+    }
+}
+
+impl FromStr for Scope {
+    type Err = EcobeeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "smartRead" => Ok(Scope::smartRead),
+            "smartWrite" => Ok(Scope::smartWrite),
+            "ems" => Ok(Scope::ems),
+            _ => Err(EcobeeError::Validation(format!("unknown scope {s:?}"))),
+        }
+    }
+}
+
+impl fmt::Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The tokens handed out by the `/token` endpoint for every grant type.
+#[derive(Debug, Deserialize)]
+pub struct RefreshTokenResponse {
+    pub access_token: Secret,
+    pub token_type: String,
+    pub refresh_token: Secret,
+    /// Seconds until `access_token` expires.
+    pub expires_in: i32,
+    pub scope: String,
+}
+
+/// The `error` codes the authorization endpoints document.
+///
+/// See <https://www.ecobee.com/home/developer/api/documentation/v1/auth/auth-req-resp.shtml>.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum TokenErrorKind {
+    access_denied,
+    invalid_request,
+    invalid_client,
+    invalid_grant,
+    unauthorized_client,
+    unsupported_grant_type,
+    invalid_scope,
+    not_supported,
+    account_locked,
+    account_disabled,
+    authorization_pending,
+    authorization_expired,
+    slow_down,
+    #[serde(other)]
+    unknown,
+}
+
+impl TokenErrorKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TokenErrorKind::access_denied => "access_denied",
+            TokenErrorKind::invalid_request => "invalid_request",
+            TokenErrorKind::invalid_client => "invalid_client",
+            TokenErrorKind::invalid_grant => "invalid_grant",
+            TokenErrorKind::unauthorized_client => "unauthorized_client",
+            TokenErrorKind::unsupported_grant_type => "unsupported_grant_type",
+            TokenErrorKind::invalid_scope => "invalid_scope",
+            TokenErrorKind::not_supported => "not_supported",
+            TokenErrorKind::account_locked => "account_locked",
+            TokenErrorKind::account_disabled => "account_disabled",
+            TokenErrorKind::authorization_pending => "authorization_pending",
+            TokenErrorKind::authorization_expired => "authorization_expired",
+            TokenErrorKind::slow_down => "slow_down",
+            TokenErrorKind::unknown => "unknown",
+        }
+    }
+}
+
+/// The error payload returned by the `/authorize` and `/token` endpoints.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct TokenError {
+    pub error: TokenErrorKind,
+    pub error_description: Option<String>,
+    pub error_uri: Option<String>,
+}
+
+impl TokenError {
+    /// Whether the refresh token or authorization code is dead for good and the account owner has
+    /// to authorize the application again.
+    pub fn requires_reauthorization(&self) -> bool {
+        matches!(
+            self.error,
+            TokenErrorKind::invalid_grant
+                | TokenErrorKind::invalid_client
+                | TokenErrorKind::authorization_expired
+                | TokenErrorKind::access_denied
+        )
+    }
+}
+
+impl fmt::Display for TokenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.error_description {
+            Some(description) => write!(f, "{}: {description}", self.error.as_str()),
+            None => f.write_str(self.error.as_str()),
+        }
+    }
+}
+
+/// Seconds added to the poll interval each time the server answers `slow_down`, as in RFC 8628.
+const SLOW_DOWN_SECONDS: i64 = 5;
+
+/// A PIN waiting for the user to enter it under "My Apps" on ecobee.com.
+///
+/// See <https://www.ecobee.com/home/developer/api/examples/ex1.shtml>.
+#[derive(Debug, Deserialize)]
+pub struct PinAuthorization {
+    /// The PIN to show to the user.
+    pub ecobeePin: String,
+    /// The authorization code exchanged for tokens once the user has entered the PIN.
+    pub code: Secret,
+    pub scope: Scope,
+    /// Minutes until the PIN can no longer be entered.
+    pub expires_in: i64,
+    /// Seconds to wait between polls of the token endpoint.
+    pub interval: i64,
+    #[serde(skip, default = "Utc::now")]
+    requested_at: DateTime<Utc>,
+}
+
+/// Ask ecobee for a PIN that the account owner can enter to grant `api_key` access to their
+/// thermostats.
+pub fn request_pin(api_key: &str, scope: Scope) -> Result<PinAuthorization, EcobeeError> {
+    let query = form_urlencode(&[
+        ("response_type", "ecobeePin"),
+        ("client_id", api_key),
+        ("scope", scope.as_str()),
+    ]);
+    read_token_response(ureq::get(&format!("{AUTHORIZE_URL}?{query}")).call())
+}
+
+impl PinAuthorization {
+    pub fn expires_at(&self) -> DateTime<Utc> {
+        self.requested_at + Duration::minutes(self.expires_in)
+    }
+
+    pub fn is_expired(&self) -> bool {
+        Utc::now() >= self.expires_at()
+    }
+
+    /// How long ecobee wants us to wait between calls to [`PinAuthorization::try_authorize`].
+    pub fn poll_interval(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.interval.max(1) as u64)
+    }
+
+    /// Check once whether the user has entered the PIN yet.
+    ///
+    /// Returns `Ok(None)` while the authorization is still pending.  When ecobee answers
+    /// `slow_down` the poll interval grows, so keep waiting [`PinAuthorization::poll_interval`]
+    /// between calls.
+    pub fn try_authorize(&mut self, api_key: &str) -> Result<Option<Ecobee>, EcobeeError> {
+        let result = token_request(&[
+            ("grant_type", "ecobeePin"),
+            ("code", self.code.expose()),
+            ("client_id", api_key),
+        ]);
+        self.poll_outcome(api_key, result)
+    }
+
+    fn poll_outcome(
+        &mut self,
+        api_key: &str,
+        result: Result<RefreshTokenResponse, EcobeeError>,
+    ) -> Result<Option<Ecobee>, EcobeeError> {
+        match result {
+            Ok(response) => Ok(Some(Ecobee::from_token_response(api_key, response))),
+            Err(EcobeeError::Token(TokenError {
+                error: TokenErrorKind::authorization_pending,
+                ..
+            })) => Ok(None),
+            Err(EcobeeError::Token(TokenError {
+                error: TokenErrorKind::slow_down,
+                ..
+            })) => {
+                self.interval = self.interval.max(1) + SLOW_DOWN_SECONDS;
+                Ok(None)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Block, polling at the server-provided interval (backing off on `slow_down`), until the
+    /// user enters the PIN or it expires.
+    pub fn wait_for_authorization(&mut self, api_key: &str) -> Result<Ecobee, EcobeeError> {
+        loop {
+            if let Some(ecobee) = self.try_authorize(api_key)? {
+                return Ok(ecobee);
+            }
+            if self.is_expired() {
+                return Err(EcobeeError::Token(TokenError {
+                    error: TokenErrorKind::authorization_expired,
+                    error_description: Some(format!(
+                        "PIN {} was not entered before it expired",
+                        self.ecobeePin
+                    )),
+                    error_uri: None,
+                }));
+            }
+            sleep(self.poll_interval());
+        }
+    }
+}
+
+/// The page to send the account owner to so they can grant `api_key` access with the
+/// authorization code flow.  ecobee redirects back to `redirect_uri` with `code` and `state`.
+pub fn authorize_url(
+    api_key: &str,
+    scope: Scope,
+    redirect_uri: &str,
+    state: Option<&str>,
+) -> String {
+    let mut params = vec![
+        ("response_type", "code"),
+        ("client_id", api_key),
+        ("redirect_uri", redirect_uri),
+        ("scope", scope.as_str()),
+    ];
+    if let Some(state) = state {
+        params.push(("state", state));
+    }
+    format!("{AUTHORIZE_URL}?{}", form_urlencode(&params))
+}
+
+/// Trade the `code` handed to `redirect_uri` for a ready client.  `redirect_uri` must match the
+/// one given to [`authorize_url`].
+pub fn exchange_authorization_code(
+    api_key: &str,
+    code: &str,
+    redirect_uri: &str,
+) -> Result<Ecobee, EcobeeError> {
+    let response = token_request(&[
+        ("grant_type", "authorization_code"),
+        ("code", code),
+        ("redirect_uri", redirect_uri),
+        ("client_id", api_key),
+    ])?;
+    Ok(Ecobee::from_token_response(api_key, response))
+}
+
+/// Trade a refresh token for a new access token.  ecobee rotates the refresh token as well, so
+/// the old one stops working once this succeeds.
+pub(crate) fn refresh_tokens(
+    api_key: &str,
+    refresh_token: &str,
+) -> Result<RefreshTokenResponse, EcobeeError> {
+    token_request(&refresh_params(api_key, refresh_token))
+}
+
+fn refresh_params<'a>(api_key: &'a str, refresh_token: &'a str) -> [(&'static str, &'a str); 3] {
+    [
+        ("grant_type", "refresh_token"),
+        ("refresh_token", refresh_token),
+        ("client_id", api_key),
+    ]
+}
+
+/// POST to the token endpoint, which is shared by every grant type.
+///
+/// Only the grant type is traced; every other parameter is a credential.
+fn token_request(params: &[(&str, &str)]) -> Result<RefreshTokenResponse, EcobeeError> {
+    let grant_type = params
+        .iter()
+        .find(|(key, _)| *key == "grant_type")
+        .map(|(_, value)| *value);
+    let span = tracing::info_span!(
+        "ecobee_token_request",
+        grant_type,
+        latency_ms = tracing::field::Empty,
+        http_status = tracing::field::Empty,
+    );
+    let _entered = span.enter();
+    let started = Instant::now();
+    let request = ureq::post(TOKEN_URL)
+        .set("Content-Type", "application/x-www-form-urlencoded")
+        .send_string(&form_urlencode(params));
+    span.record("latency_ms", started.elapsed().as_millis() as u64);
+    let http_status = match &request {
+        Ok(response) => Some(response.status()),
+        Err(ureq::Error::Status(http_status, _)) => Some(*http_status),
+        Err(ureq::Error::Transport(_)) => None,
+    };
+    if let Some(http_status) = http_status {
+        span.record("http_status", http_status);
+    }
+    let result = read_token_response(request);
+    if let Err(error) = &result {
+        tracing::warn!(%error, "token request failed");
+    }
+    result
+}
+
+/// Unlike the `/1/` API, the authorization endpoints report failures as OAuth error payloads.
+///
+/// Successful bodies carry tokens, so they are never kept around in a deserialization error.
+fn read_token_response<T: serde::de::DeserializeOwned>(
+    result: Result<ureq::Response, ureq::Error>,
+) -> Result<T, EcobeeError> {
+    let withhold_body = |e| match e {
+        EcobeeError::Deserialize { source, .. } => EcobeeError::Deserialize {
+            source,
+            body: "<token response withheld>".to_string(),
+        },
+        e => e,
+    };
+    match result {
+        Ok(response) => {
+            let body = response.into_string()?;
+            #[cfg(feature = "trace-bodies")]
+            tracing::trace!(body = %crate::secret::redact_json(&body, crate::secret::TOKEN_SECRET_KEYS), "token response body");
+            parse_body(body).map_err(withhold_body)
+        }
+        Err(ureq::Error::Status(_, response)) => {
+            let error: TokenError = parse_body(response.into_string()?).map_err(withhold_body)?;
+            Err(EcobeeError::Token(error))
+        }
+        Err(ureq::Error::Transport(e)) => Err(e.into()),
+    }
+}
+
+fn form_urlencode(params: &[(&str, &str)]) -> String {
+    params
+        .iter()
+        .map(|(key, value)| format!("{}={}", key, urlencoding::encode(value)))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pin_response() {
+        let pin: PinAuthorization = serde_json::from_str(
+            r#"{"ecobeePin":"bv29","code":"uiM8JbaXhgoVmHVmBmdTtsfeBpwiurbj","scope":"smartWrite","expires_in":9,"interval":30}"#,
+        )
+        .unwrap();
+        assert_eq!(pin.ecobeePin, "bv29");
+        assert_eq!(pin.scope, Scope::smartWrite);
+        assert_eq!(pin.expires_at() - pin.requested_at, Duration::minutes(9));
+        assert_eq!(pin.poll_interval(), std::time::Duration::from_secs(30));
+        assert!(!pin.is_expired());
+    }
+
+    #[test]
+    fn slow_down_backs_off() {
+        let mut pin: PinAuthorization = serde_json::from_str(
+            r#"{"ecobeePin":"bv29","code":"c","scope":"smartWrite","expires_in":9,"interval":30}"#,
+        )
+        .unwrap();
+        let refused = |error| {
+            Err(EcobeeError::Token(TokenError {
+                error,
+                error_description: None,
+                error_uri: None,
+            }))
+        };
+        let pending = pin.poll_outcome("key", refused(TokenErrorKind::authorization_pending));
+        assert!(matches!(pending, Ok(None)));
+        assert_eq!(pin.poll_interval(), std::time::Duration::from_secs(30));
+        let slow_down = pin.poll_outcome("key", refused(TokenErrorKind::slow_down));
+        assert!(matches!(slow_down, Ok(None)));
+        assert_eq!(pin.poll_interval(), std::time::Duration::from_secs(35));
+        let denied = pin.poll_outcome("key", refused(TokenErrorKind::access_denied));
+        assert!(matches!(denied, Err(EcobeeError::Token(_))));
+    }
+
+    #[test]
+    fn token_error_payload() {
+        let error: TokenError = serde_json::from_str(
+            r#"{"error":"authorization_pending","error_description":"Waiting for user to authorize application.","error_uri":"https://tools.ietf.org/html/rfc6749#section-5.2"}"#,
+        )
+        .unwrap();
+        assert_eq!(error.error, TokenErrorKind::authorization_pending);
+        assert_eq!(
+            error.to_string(),
+            "authorization_pending: Waiting for user to authorize application."
+        );
+        assert!(!error.requires_reauthorization());
+
+        let error: TokenError = serde_json::from_str(r#"{"error":"something_new"}"#).unwrap();
+        assert_eq!(error.error, TokenErrorKind::unknown);
+    }
+
+    #[test]
+    fn refresh_errors_are_typed() {
+        let response = ureq::Response::new(
+            400,
+            "Bad Request",
+            r#"{"error":"invalid_grant","error_description":"The authorization grant, token or credentials are invalid, expired, revoked, do not match the redirection URI, or were issued to another client."}"#,
+        )
+        .unwrap();
+        match read_token_response::<RefreshTokenResponse>(Err(ureq::Error::Status(400, response))) {
+            Err(EcobeeError::Token(e)) => {
+                assert_eq!(e.error, TokenErrorKind::invalid_grant);
+                assert!(e.requires_reauthorization());
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn unreadable_token_response_is_withheld() {
+        let response =
+            ureq::Response::new(200, "OK", r#"{"access_token":"secret-access"}"#).unwrap();
+        match read_token_response::<RefreshTokenResponse>(Ok(response)) {
+            Err(EcobeeError::Deserialize { body, .. }) => assert!(!body.contains("secret")),
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn refresh_form() {
+        assert_eq!(
+            form_urlencode(&refresh_params("my key", "rT/k+n=")),
+            "grant_type=refresh_token&refresh_token=rT%2Fk%2Bn%3D&client_id=my%20key"
+        );
+    }
+
+    #[test]
+    fn granted_scope() {
+        assert_eq!(Scope::from_granted("smartRead"), Some(Scope::smartRead));
+        assert_eq!(
+            Scope::from_granted("openid,smartWrite offline_access"),
+            Some(Scope::smartWrite)
+        );
+        assert_eq!(Scope::from_granted("smartRead,ems"), Some(Scope::ems));
+        assert_eq!(Scope::from_granted("openid"), None);
+        assert!(!Scope::smartRead.allows_write());
+        assert!(Scope::ems.allows_write());
+    }
+
+    #[test]
+    fn authorization_code_url() {
+        assert_eq!(
+            authorize_url(
+                "KEY",
+                Scope::smartRead,
+                "https://example.com/cb?x=1",
+                Some("xyz")
+            ),
+            "https://api.ecobee.com/authorize?response_type=code&client_id=KEY&redirect_uri=https%3A%2F%2Fexample.com%2Fcb%3Fx%3D1&scope=smartRead&state=xyz"
+        );
+    }
+
+    #[test]
+    fn form_values_are_encoded() {
+        assert_eq!(
+            form_urlencode(&[("grant_type", "ecobeePin"), ("code", "a+b/c=")]),
+            "grant_type=ecobeePin&code=a%2Bb%2Fc%3D"
+        );
+    }
+}
@@ -0,0 +1,174 @@
+use crate::{Scope, Status, TokenError};
+use std::fmt;
+
+/// Everything that can go wrong while talking to the ecobee API.
+#[derive(Debug)]
+pub enum EcobeeError {
+    /// The request never got an HTTP response (DNS, TLS, connection reset, ...).
+    ///
+    /// Only the kind and message of the underlying error are kept: its URL can carry the API key
+    /// in the query string.
+    Transport {
+        kind: ureq::ErrorKind,
+        message: Option<String>,
+    },
+    /// The response arrived but its body could not be read.
+    Io(std::io::Error),
+    /// The server answered with a non-success HTTP status and an ecobee status object.
+    Status { http_status: u16, status: Status },
+    /// The access token has expired (ecobee status code 14) and needs a refresh.
+    AuthExpired(Status),
+    /// The access token is not valid or was deauthorized by the user (ecobee status code 16).
+    InvalidToken(Status),
+    /// A response body did not have the shape we expected.
+    Deserialize {
+        source: serde_json::Error,
+        body: String,
+    },
+    /// The authorization server refused a PIN, code or token exchange.
+    Token(TokenError),
+    /// The tokens were granted a scope that does not permit this call.
+    InsufficientScope {
+        call: &'static str,
+        required: Scope,
+        granted: Scope,
+    },
+    /// Tokens could not be loaded from or saved to the configured `TokenStore`.
+    TokenStore(std::io::Error),
+    /// The request was refused locally before anything was sent.
+    Validation(String),
+    /// The access token was refreshed after expiring, but replaying the request still failed.
+    RetryFailed {
+        endpoint: &'static str,
+        source: Box<EcobeeError>,
+    },
+}
+
+impl EcobeeError {
+    /// ecobee status code for an expired access token.
+    pub const AUTH_EXPIRED_CODE: i32 = 14;
+    /// ecobee status code for a deauthorized or otherwise invalid access token.
+    pub const INVALID_TOKEN_CODE: i32 = 16;
+
+    /// Sort an ecobee status object returned alongside a non-success HTTP status.
+    pub fn from_status(http_status: u16, status: Status) -> Self {
+        match status.code {
+            Self::AUTH_EXPIRED_CODE => EcobeeError::AuthExpired(status),
+            Self::INVALID_TOKEN_CODE => EcobeeError::InvalidToken(status),
+            _ => EcobeeError::Status {
+                http_status,
+                status,
+            },
+        }
+    }
+
+    /// The ecobee status object carried by this error, if the server sent one.
+    pub fn status(&self) -> Option<&Status> {
+        match self {
+            EcobeeError::Status { status, .. }
+            | EcobeeError::AuthExpired(status)
+            | EcobeeError::InvalidToken(status) => Some(status),
+            EcobeeError::RetryFailed { source, .. } => source.status(),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for EcobeeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EcobeeError::Transport {
+                kind,
+                message: Some(message),
+            } => write!(f, "transport error: {kind}: {message}"),
+            EcobeeError::Transport {
+                kind,
+                message: None,
+            } => write!(f, "transport error: {kind}"),
+            EcobeeError::Io(e) => write!(f, "failed to read response body: {e}"),
+            EcobeeError::Status {
+                http_status,
+                status,
+            } => write!(
+                f,
+                "ecobee returned HTTP {http_status} with status {}: {}",
+                status.code, status.message
+            ),
+            EcobeeError::AuthExpired(status) => {
+                write!(f, "access token has expired: {}", status.message)
+            }
+            EcobeeError::InvalidToken(status) => {
+                write!(f, "access token is not valid: {}", status.message)
+            }
+            EcobeeError::Deserialize { source, .. } => {
+                write!(f, "failed to deserialize response body: {source}")
+            }
+            EcobeeError::Token(e) => write!(f, "authorization failed: {e}"),
+            EcobeeError::InsufficientScope {
+                call,
+                required,
+                granted,
+            } => write!(
+                f,
+                "{call} needs the {required} scope but the tokens were only granted {granted}"
+            ),
+            EcobeeError::TokenStore(e) => write!(f, "token store failed: {e}"),
+            EcobeeError::Validation(reason) => write!(f, "invalid request: {reason}"),
+            EcobeeError::RetryFailed { endpoint, source } => write!(
+                f,
+                "{endpoint} request failed again after refreshing the access token: {source}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for EcobeeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            EcobeeError::Io(e) | EcobeeError::TokenStore(e) => Some(e),
+            EcobeeError::Deserialize { source, .. } => Some(source),
+            EcobeeError::RetryFailed { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<ureq::Transport> for EcobeeError {
+    fn from(e: ureq::Transport) -> Self {
+        use std::error::Error;
+
+        let message = match (e.message(), e.source()) {
+            (Some(message), Some(source)) => Some(format!("{message}: {source}")),
+            (Some(message), None) => Some(message.to_string()),
+            (None, Some(source)) => Some(source.to_string()),
+            (None, None) => None,
+        };
+        EcobeeError::Transport {
+            kind: e.kind(),
+            message,
+        }
+    }
+}
+
+impl From<std::io::Error> for EcobeeError {
+    fn from(e: std::io::Error) -> Self {
+        EcobeeError::Io(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transport_errors_leave_out_the_url() {
+        // Nothing listens on port 1, so this fails without leaving the machine.
+        let e = match ureq::get("http://127.0.0.1:1/authorize?client_id=hunter2").call() {
+            Err(ureq::Error::Transport(e)) => EcobeeError::from(e),
+            other => panic!("expected a transport error, got {:?}", other.map(|_| ())),
+        };
+        assert!(matches!(e, EcobeeError::Transport { .. }));
+        assert!(!e.to_string().contains("hunter2"), "{}", e);
+        assert!(!format!("{e:?}").contains("hunter2"), "{:?}", e);
+    }
+}
@@ -0,0 +1,677 @@
+//! Thermostat functions, the actions `POST /thermostat` can run besides writing properties
+//! (<https://www.ecobee.com/home/developer/api/documentation/v1/functions/using-functions.shtml>).
+
+use crate::{EcobeeError, ThermostatId};
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use serde::ser::SerializeMap;
+use serde::{Deserialize, Serialize, Serializer};
+
+/// A function to run on every thermostat in the request's selection.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", content = "params")]
+pub enum Function {
+    setHold(SetHold),
+    resumeProgram(ResumeProgram),
+    setOccupied(SetOccupied),
+    acknowledge(Acknowledge),
+    createVacation(CreateVacation),
+    deleteVacation(DeleteVacation),
+    sendMessage(SendMessage),
+    updateSensor(UpdateSensor),
+    controlPlug(ControlPlug),
+    /// Put every thermostat setting back to its factory default.  The program, vacations and
+    /// sensor names are lost too.
+    resetPreferences,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FanMode {
+    auto,
+    on,
+    /// A value this crate does not know about yet, kept as ecobee sent it so it can be written
+    /// back unchanged.
+    #[serde(untagged)]
+    unknown(String),
+}
+
+/// How long a hold lasts.  Dates and times are in the thermostat's own time zone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HoldType {
+    /// From `start` until `end`.
+    dateTime {
+        start: NaiveDateTime,
+        end: NaiveDateTime,
+    },
+    /// Until the program moves on to its next climate.
+    nextTransition,
+    /// Until something else replaces it.
+    indefinite,
+    /// For this many hours from now.
+    holdHours(u32),
+}
+
+impl HoldType {
+    fn as_str(&self) -> &'static str {
+        match self {
+            HoldType::dateTime { .. } => "dateTime",
+            HoldType::nextTransition => "nextTransition",
+            HoldType::indefinite => "indefinite",
+            HoldType::holdHours(_) => "holdHours",
+        }
+    }
+
+    fn validate(&self) -> Result<(), EcobeeError> {
+        match self {
+            HoldType::dateTime { start, end } if end <= start => Err(EcobeeError::Validation(
+                format!("a hold cannot end ({end}) before it starts ({start})"),
+            )),
+            HoldType::holdHours(0) => Err(EcobeeError::Validation(
+                "a hold must last at least one hour".to_string(),
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum HoldTarget {
+    /// Tenths of a degree Fahrenheit.
+    Temperatures {
+        cool: i32,
+        heat: i32,
+    },
+    Climate(String),
+}
+
+/// Parameters of the `setHold` function, which overrides the program with fixed setpoints or
+/// one of its climates.
+///
+/// ```
+/// use ecobee::{FanMode, Function, HoldType, SetHold};
+///
+/// let hold = SetHold::temperatures(760, 680, HoldType::holdHours(2))?.fan(FanMode::on);
+/// let function = Function::setHold(hold);
+/// # Ok::<(), ecobee::EcobeeError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SetHold {
+    target: HoldTarget,
+    holdType: HoldType,
+    fan: Option<FanMode>,
+}
+
+impl SetHold {
+    /// Hold at fixed setpoints, in tenths of a degree Fahrenheit.  ecobee wants both even when
+    /// the thermostat is only heating or only cooling.
+    pub fn temperatures(cool: i32, heat: i32, hold_type: HoldType) -> Result<Self, EcobeeError> {
+        if heat > cool {
+            return Err(EcobeeError::Validation(format!(
+                "heat hold temperature {heat} is above cool hold temperature {cool}"
+            )));
+        }
+        Self::new(HoldTarget::Temperatures { cool, heat }, hold_type)
+    }
+
+    /// Hold at the setpoints of one of the program's climates, by its `climateRef`.
+    pub fn climate(
+        climate_ref: impl Into<String>,
+        hold_type: HoldType,
+    ) -> Result<Self, EcobeeError> {
+        let climate_ref = climate_ref.into();
+        if climate_ref.is_empty() {
+            return Err(EcobeeError::Validation(
+                "hold climate ref cannot be empty".to_string(),
+            ));
+        }
+        Self::new(HoldTarget::Climate(climate_ref), hold_type)
+    }
+
+    fn new(target: HoldTarget, hold_type: HoldType) -> Result<Self, EcobeeError> {
+        hold_type.validate()?;
+        Ok(SetHold {
+            target,
+            holdType: hold_type,
+            fan: None,
+        })
+    }
+
+    /// Also hold the fan in this mode.
+    pub fn fan(mut self, fan: FanMode) -> Self {
+        self.fan = Some(fan);
+        self
+    }
+
+    pub fn hold_type(&self) -> HoldType {
+        self.holdType
+    }
+}
+
+fn date(date: NaiveDate) -> String {
+    date.format("%Y-%m-%d").to_string()
+}
+
+fn time(time: NaiveTime) -> String {
+    time.format("%H:%M:%S").to_string()
+}
+
+impl Serialize for SetHold {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(None)?;
+        match &self.target {
+            HoldTarget::Temperatures { cool, heat } => {
+                map.serialize_entry("coolHoldTemp", cool)?;
+                map.serialize_entry("heatHoldTemp", heat)?;
+            }
+            HoldTarget::Climate(climate_ref) => {
+                map.serialize_entry("holdClimateRef", climate_ref)?;
+            }
+        }
+        if let Some(fan) = &self.fan {
+            map.serialize_entry("fan", fan)?;
+        }
+        serialize_hold_type(&mut map, self.holdType)?;
+        map.end()
+    }
+}
+
+/// Parameters of the `resumeProgram` function, which cancels the running hold.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ResumeProgram {
+    /// Cancel every stacked hold instead of only the topmost one.
+    pub resumeAll: bool,
+}
+
+/// Parameters of the `setOccupied` function, which marks an EMS thermostat occupied or not for
+/// a while.  Only EMS thermostats accept it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SetOccupied {
+    occupied: bool,
+    holdType: HoldType,
+}
+
+impl SetOccupied {
+    pub fn new(occupied: bool, hold_type: HoldType) -> Result<Self, EcobeeError> {
+        hold_type.validate()?;
+        Ok(SetOccupied {
+            occupied,
+            holdType: hold_type,
+        })
+    }
+
+    pub fn occupied(&self) -> bool {
+        self.occupied
+    }
+
+    pub fn hold_type(&self) -> HoldType {
+        self.holdType
+    }
+}
+
+impl Serialize for SetOccupied {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(None)?;
+        map.serialize_entry("occupied", &self.occupied)?;
+        serialize_hold_type(&mut map, self.holdType)?;
+        map.end()
+    }
+}
+
+/// The `holdType` entry and the ones that go with it, shared by every function taking a hold.
+fn serialize_hold_type<M: SerializeMap>(map: &mut M, hold_type: HoldType) -> Result<(), M::Error> {
+    map.serialize_entry("holdType", hold_type.as_str())?;
+    match hold_type {
+        HoldType::dateTime { start, end } => {
+            map.serialize_entry("startDate", &date(start.date()))?;
+            map.serialize_entry("startTime", &time(start.time()))?;
+            map.serialize_entry("endDate", &date(end.date()))?;
+            map.serialize_entry("endTime", &time(end.time()))?;
+        }
+        HoldType::holdHours(hours) => map.serialize_entry("holdHours", &hours)?,
+        HoldType::nextTransition | HoldType::indefinite => {}
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum AckType {
+    accept,
+    decline,
+    defer,
+    unacknowledged,
+}
+
+/// Parameters of the `acknowledge` function, which answers an alert from
+/// [`Thermostat::alerts`](crate::Thermostat::alerts).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Acknowledge {
+    pub thermostatIdentifier: ThermostatId,
+    /// The alert's `acknowledgeRef`.
+    pub ackRef: String,
+    pub ackType: AckType,
+    /// Ask to be reminded of a deferred alert later.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remindMeLater: Option<bool>,
+}
+
+/// Parameters of the `createVacation` function, which adds a vacation event holding fixed
+/// setpoints from `start` to `end`, both in the thermostat's own time zone.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CreateVacation {
+    name: String,
+    coolHoldTemp: i32,
+    heatHoldTemp: i32,
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+    fan: Option<FanMode>,
+    fanMinOnTime: Option<u32>,
+}
+
+impl CreateVacation {
+    /// Temperatures are in tenths of a degree Fahrenheit.
+    pub fn new(
+        name: impl Into<String>,
+        cool: i32,
+        heat: i32,
+        start: NaiveDateTime,
+        end: NaiveDateTime,
+    ) -> Result<Self, EcobeeError> {
+        let name = name.into();
+        if name.is_empty() {
+            return Err(EcobeeError::Validation(
+                "vacation name cannot be empty".to_string(),
+            ));
+        }
+        if heat > cool {
+            return Err(EcobeeError::Validation(format!(
+                "heat hold temperature {heat} is above cool hold temperature {cool}"
+            )));
+        }
+        HoldType::dateTime { start, end }.validate()?;
+        Ok(CreateVacation {
+            name,
+            coolHoldTemp: cool,
+            heatHoldTemp: heat,
+            start,
+            end,
+            fan: None,
+            fanMinOnTime: None,
+        })
+    }
+
+    pub fn fan(mut self, fan: FanMode) -> Self {
+        self.fan = Some(fan);
+        self
+    }
+
+    /// Run the fan at least this many minutes an hour, at most 60.
+    pub fn fan_min_on_time(mut self, minutes: u32) -> Result<Self, EcobeeError> {
+        if minutes > 60 {
+            return Err(EcobeeError::Validation(format!(
+                "fan minimum on time is minutes per hour, {minutes} is more than 60"
+            )));
+        }
+        self.fanMinOnTime = Some(minutes);
+        Ok(self)
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl Serialize for CreateVacation {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(None)?;
+        map.serialize_entry("name", &self.name)?;
+        map.serialize_entry("coolHoldTemp", &self.coolHoldTemp)?;
+        map.serialize_entry("heatHoldTemp", &self.heatHoldTemp)?;
+        map.serialize_entry("startDate", &date(self.start.date()))?;
+        map.serialize_entry("startTime", &time(self.start.time()))?;
+        map.serialize_entry("endDate", &date(self.end.date()))?;
+        map.serialize_entry("endTime", &time(self.end.time()))?;
+        if let Some(fan) = &self.fan {
+            map.serialize_entry("fan", fan)?;
+        }
+        if let Some(minutes) = &self.fanMinOnTime {
+            map.serialize_entry("fanMinOnTime", minutes)?;
+        }
+        map.end()
+    }
+}
+
+/// Parameters of the `deleteVacation` function.  Vacations are picked by name.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DeleteVacation {
+    pub name: String,
+}
+
+/// The longest message a thermostat will display, in characters.
+pub const MAX_MESSAGE_LENGTH: usize = 500;
+
+/// Parameters of the `sendMessage` function, which pops a message up on the thermostat screen.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SendMessage {
+    text: String,
+}
+
+impl SendMessage {
+    pub fn new(text: impl Into<String>) -> Result<Self, EcobeeError> {
+        let text = text.into();
+        if text.is_empty() {
+            return Err(EcobeeError::Validation(
+                "message text cannot be empty".to_string(),
+            ));
+        }
+        let length = text.chars().count();
+        if length > MAX_MESSAGE_LENGTH {
+            return Err(EcobeeError::Validation(format!(
+                "messages are limited to {MAX_MESSAGE_LENGTH} characters, got {length}"
+            )));
+        }
+        Ok(SendMessage { text })
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+}
+
+/// The longest name a remote sensor can have, in characters.
+pub const MAX_SENSOR_NAME_LENGTH: usize = 32;
+
+/// Parameters of the `updateSensor` function, which renames a remote sensor.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct UpdateSensor {
+    name: String,
+    deviceId: String,
+    sensorId: String,
+}
+
+impl UpdateSensor {
+    /// `device_id` is the [`RemoteSensor::id`](crate::RemoteSensor::id), such as `rs:100`, and
+    /// `sensor_id` is the input within that device, such as `1`.
+    pub fn new(
+        name: impl Into<String>,
+        device_id: impl Into<String>,
+        sensor_id: impl Into<String>,
+    ) -> Result<Self, EcobeeError> {
+        let name = name.into();
+        if name.is_empty() {
+            return Err(EcobeeError::Validation(
+                "sensor name cannot be empty".to_string(),
+            ));
+        }
+        let length = name.chars().count();
+        if length > MAX_SENSOR_NAME_LENGTH {
+            return Err(EcobeeError::Validation(format!(
+                "sensor names are limited to {MAX_SENSOR_NAME_LENGTH} characters, got {length}"
+            )));
+        }
+        Ok(UpdateSensor {
+            name,
+            deviceId: device_id.into(),
+            sensorId: sensor_id.into(),
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn device_id(&self) -> &str {
+        &self.deviceId
+    }
+
+    pub fn sensor_id(&self) -> &str {
+        &self.sensorId
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum PlugState {
+    on,
+    off,
+    /// Go back to following the plug's own program.
+    resume,
+}
+
+/// Parameters of the `controlPlug` function, which switches a smart plug or relay.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ControlPlug {
+    plugName: String,
+    plugState: PlugState,
+    holdType: Option<HoldType>,
+}
+
+impl ControlPlug {
+    /// Switching `on` or `off` needs a hold saying for how long; `resume` ends the hold, so it
+    /// cannot take one.
+    pub fn new(
+        plug_name: impl Into<String>,
+        plug_state: PlugState,
+        hold_type: Option<HoldType>,
+    ) -> Result<Self, EcobeeError> {
+        let plug_name = plug_name.into();
+        if plug_name.is_empty() {
+            return Err(EcobeeError::Validation(
+                "plug name cannot be empty".to_string(),
+            ));
+        }
+        match (plug_state, hold_type) {
+            (PlugState::resume, Some(hold_type)) => {
+                return Err(EcobeeError::Validation(format!(
+                    "resuming a plug's program cannot take a {} hold",
+                    hold_type.as_str()
+                )))
+            }
+            (PlugState::on | PlugState::off, None) => {
+                return Err(EcobeeError::Validation(
+                    "switching a plug on or off needs a hold type".to_string(),
+                ))
+            }
+            (_, Some(hold_type)) => hold_type.validate()?,
+            (PlugState::resume, None) => {}
+        }
+        Ok(ControlPlug {
+            plugName: plug_name,
+            plugState: plug_state,
+            holdType: hold_type,
+        })
+    }
+}
+
+impl Serialize for ControlPlug {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(None)?;
+        map.serialize_entry("plugName", &self.plugName)?;
+        map.serialize_entry("plugState", &self.plugState)?;
+        if let Some(hold_type) = self.holdType {
+            serialize_hold_type(&mut map, hold_type)?;
+        }
+        map.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn json(function: Function) -> String {
+        serde_json::to_string(&function).unwrap()
+    }
+
+    #[test]
+    fn temperature_holds() {
+        let hold = SetHold::temperatures(760, 680, HoldType::nextTransition).unwrap();
+        assert_eq!(
+            json(Function::setHold(hold)),
+            r#"{"type":"setHold","params":{"coolHoldTemp":760,"heatHoldTemp":680,"holdType":"nextTransition"}}"#
+        );
+        let hold = SetHold::temperatures(760, 680, HoldType::holdHours(2))
+            .unwrap()
+            .fan(FanMode::on);
+        assert_eq!(
+            json(Function::setHold(hold)),
+            r#"{"type":"setHold","params":{"coolHoldTemp":760,"heatHoldTemp":680,"fan":"on","holdType":"holdHours","holdHours":2}}"#
+        );
+        assert!(SetHold::temperatures(680, 760, HoldType::indefinite).is_err());
+        assert!(SetHold::temperatures(760, 680, HoldType::holdHours(0)).is_err());
+    }
+
+    #[test]
+    fn other_functions() {
+        assert_eq!(
+            json(Function::resumeProgram(ResumeProgram { resumeAll: true })),
+            r#"{"type":"resumeProgram","params":{"resumeAll":true}}"#
+        );
+        let occupied = SetOccupied::new(false, HoldType::holdHours(4)).unwrap();
+        assert_eq!(
+            json(Function::setOccupied(occupied)),
+            r#"{"type":"setOccupied","params":{"occupied":false,"holdType":"holdHours","holdHours":4}}"#
+        );
+        assert!(SetOccupied::new(true, HoldType::holdHours(0)).is_err());
+        let acknowledge = Acknowledge {
+            thermostatIdentifier: "522697894617".parse().unwrap(),
+            ackRef: "ref-1".to_string(),
+            ackType: AckType::defer,
+            remindMeLater: Some(true),
+        };
+        assert_eq!(
+            json(Function::acknowledge(acknowledge)),
+            r#"{"type":"acknowledge","params":{"thermostatIdentifier":"522697894617","ackRef":"ref-1","ackType":"defer","remindMeLater":true}}"#
+        );
+    }
+
+    #[test]
+    fn vacations() {
+        let start = "2022-07-01T08:00:00".parse::<NaiveDateTime>().unwrap();
+        let end = "2022-07-15T18:00:00".parse::<NaiveDateTime>().unwrap();
+        let vacation = CreateVacation::new("Cottage", 820, 600, start, end)
+            .unwrap()
+            .fan(FanMode::auto)
+            .fan_min_on_time(10)
+            .unwrap();
+        assert_eq!(
+            json(Function::createVacation(vacation)),
+            r#"{"type":"createVacation","params":{"name":"Cottage","coolHoldTemp":820,"heatHoldTemp":600,"startDate":"2022-07-01","startTime":"08:00:00","endDate":"2022-07-15","endTime":"18:00:00","fan":"auto","fanMinOnTime":10}}"#
+        );
+        assert!(CreateVacation::new("", 820, 600, start, end).is_err());
+        assert!(CreateVacation::new("Cottage", 820, 600, end, start).is_err());
+        assert!(CreateVacation::new("Cottage", 820, 600, start, end)
+            .unwrap()
+            .fan_min_on_time(61)
+            .is_err());
+        assert_eq!(
+            json(Function::deleteVacation(DeleteVacation {
+                name: "Cottage".to_string()
+            })),
+            r#"{"type":"deleteVacation","params":{"name":"Cottage"}}"#
+        );
+    }
+
+    #[test]
+    fn messages() {
+        let message = SendMessage::new("HVAC maintenance Tuesday").unwrap();
+        assert_eq!(
+            json(Function::sendMessage(message)),
+            r#"{"type":"sendMessage","params":{"text":"HVAC maintenance Tuesday"}}"#
+        );
+        assert!(SendMessage::new("").is_err());
+        assert!(SendMessage::new("é".repeat(MAX_MESSAGE_LENGTH)).is_ok());
+        assert!(SendMessage::new("é".repeat(MAX_MESSAGE_LENGTH + 1)).is_err());
+    }
+
+    #[test]
+    fn sensor_rename() {
+        let update = UpdateSensor::new("Back Door", "rs:101", "1").unwrap();
+        assert_eq!(
+            json(Function::updateSensor(update)),
+            r#"{"type":"updateSensor","params":{"name":"Back Door","deviceId":"rs:101","sensorId":"1"}}"#
+        );
+        assert!(UpdateSensor::new("", "rs:101", "1").is_err());
+        assert!(UpdateSensor::new("x".repeat(MAX_SENSOR_NAME_LENGTH), "rs:101", "1").is_ok());
+        assert!(UpdateSensor::new("x".repeat(MAX_SENSOR_NAME_LENGTH + 1), "rs:101", "1").is_err());
+    }
+
+    #[test]
+    fn plugs() {
+        let plug = ControlPlug::new("Humidifier", PlugState::on, Some(HoldType::holdHours(3)));
+        assert_eq!(
+            json(Function::controlPlug(plug.unwrap())),
+            r#"{"type":"controlPlug","params":{"plugName":"Humidifier","plugState":"on","holdType":"holdHours","holdHours":3}}"#
+        );
+        let start = "2022-01-15T22:00:00".parse::<NaiveDateTime>().unwrap();
+        let end = "2022-01-16T06:30:00".parse::<NaiveDateTime>().unwrap();
+        let plug = ControlPlug::new(
+            "Humidifier",
+            PlugState::off,
+            Some(HoldType::dateTime { start, end }),
+        );
+        assert_eq!(
+            json(Function::controlPlug(plug.unwrap())),
+            r#"{"type":"controlPlug","params":{"plugName":"Humidifier","plugState":"off","holdType":"dateTime","startDate":"2022-01-15","startTime":"22:00:00","endDate":"2022-01-16","endTime":"06:30:00"}}"#
+        );
+        let plug = ControlPlug::new("Humidifier", PlugState::resume, None);
+        assert_eq!(
+            json(Function::controlPlug(plug.unwrap())),
+            r#"{"type":"controlPlug","params":{"plugName":"Humidifier","plugState":"resume"}}"#
+        );
+        assert!(
+            ControlPlug::new("Humidifier", PlugState::resume, Some(HoldType::indefinite)).is_err()
+        );
+        assert!(ControlPlug::new("Humidifier", PlugState::on, None).is_err());
+        assert!(
+            ControlPlug::new("Humidifier", PlugState::on, Some(HoldType::holdHours(0))).is_err()
+        );
+        assert!(ControlPlug::new("", PlugState::resume, None).is_err());
+    }
+
+    #[test]
+    fn unknown_fan_mode() {
+        let json = r#"{"coolFan":"smart","heatFan":"auto"}"#;
+        let climate: crate::Climate = serde_json::from_str(json).unwrap();
+        assert_eq!(climate.coolFan, Some(FanMode::unknown("smart".to_string())));
+        assert_eq!(climate.heatFan, Some(FanMode::auto));
+        assert_eq!(serde_json::to_string(&climate).unwrap(), json);
+    }
+
+    #[test]
+    fn reset_preferences() {
+        assert_eq!(
+            json(Function::resetPreferences),
+            r#"{"type":"resetPreferences"}"#
+        );
+    }
+
+    #[test]
+    fn climate_holds() {
+        let start = "2022-01-15T22:00:00".parse::<NaiveDateTime>().unwrap();
+        let end = "2022-01-16T06:30:00".parse::<NaiveDateTime>().unwrap();
+        let hold = SetHold::climate("sleep", HoldType::dateTime { start, end }).unwrap();
+        assert_eq!(
+            json(Function::setHold(hold)),
+            r#"{"type":"setHold","params":{"holdClimateRef":"sleep","holdType":"dateTime","startDate":"2022-01-15","startTime":"22:00:00","endDate":"2022-01-16","endTime":"06:30:00"}}"#
+        );
+        assert!(SetHold::climate("", HoldType::indefinite).is_err());
+        assert!(SetHold::climate(
+            "sleep",
+            HoldType::dateTime {
+                start: end,
+                end: start
+            }
+        )
+        .is_err());
+    }
+}
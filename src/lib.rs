@@ -1,72 +1,60 @@
-#![feature(in_band_lifetimes)]
 #![allow(non_camel_case_types)]
 #![allow(non_snake_case)]
-
-use serde::{Deserialize, Serialize, Serializer};
+// Request and response bodies can carry credentials, so nothing in the crate prints on its own.
+#![deny(clippy::dbg_macro, clippy::print_stdout, clippy::print_stderr)]
+
+mod auth;
+mod error;
+mod function;
+mod page;
+mod program;
+mod runtime_report;
+mod schedule;
+mod secret;
+mod selection;
+mod thermostat;
+mod token_store;
+
+pub use auth::{
+    authorize_url, exchange_authorization_code, request_pin, PinAuthorization,
+    RefreshTokenResponse, Scope, TokenError, TokenErrorKind,
+};
+pub use error::EcobeeError;
+pub use function::{
+    AckType, Acknowledge, ControlPlug, CreateVacation, DeleteVacation, FanMode, Function, HoldType,
+    PlugState, ResumeProgram, SendMessage, SetHold, SetOccupied, UpdateSensor, MAX_MESSAGE_LENGTH,
+    MAX_SENSOR_NAME_LENGTH,
+};
+pub use page::{Page, ThermostatsIter};
+pub use runtime_report::{RuntimeColumn, MAX_RUNTIME_COLUMNS};
+pub use schedule::{ScheduleChange, ScheduleClimate, ScheduleFile};
+pub use secret::Secret;
+pub use selection::{
+    ManagementSetPath, Selection, SelectionInclude, SelectionType, ThermostatId,
+    MAX_THERMOSTATS_PER_SELECTION,
+};
+pub use thermostat::{
+    Action, Alert, Audio, Capabilities, Climate, ClimateSensor, DehumidifierMode, Device,
+    Electricity, ElectricityDevice, ElectricityTier, Energy, EquipmentSetting, Event,
+    ExtendedRuntime, GeneralSetting, HouseDetails, HumidifierMode, HvacMode, LimitSetting,
+    Location, Management, NotificationSettings, OemCfg, Output, Privacy, Program, RemoteSensor,
+    RemoteSensorCapability, RemoteSensorType, Runtime, SecuritySettings, Sensor, SensorReading,
+    Settings, State, Technician, Thermostat, ThermostatReminder, Utility, Vent, Version,
+    VoiceEngine, Weather, WeatherForecast,
+};
+pub use token_store::{FileTokenStore, MemoryTokenStore, StoredTokens, TokenStore};
+
+use chrono::{DateTime, Duration, Utc};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+use std::fmt;
 use std::str::FromStr;
-use ureq::Error;
-
-#[derive(Debug, Serialize)]
-pub enum SelectionType {
-    registered,
-    thermostats,
-    managementSet,
-}
-
-#[derive(Debug, Serialize)]
-pub enum SelectionInclude {
-    includeRuntime,
-    includeExtendedRuntime,
-    includeElectricity,
-    includeSettings,
-    includeLocation,
-    includeProgram,
-    includeEvents,
-    includeDevice,
-    includeTechnician,
-    includeUtility,
-    includeManagement,
-    includeAlerts,
-    includeReminders,
-    includeWeather,
-    includeHouseDetails,
-    includeOemCfg,
-    includeEquipmentStatus,
-    includeNotificationSettings,
-    includePrivacy,
-    includeVersion,
-    includeSecuritySettings,
-    includeSensors,
-    includeAudio,
-    includeEnergy,
-    includeCapabilities,
-}
+use std::time::Instant;
 
-#[derive(Debug)]
-pub struct Selection {
-    pub selectionType: SelectionType,
-    pub selectionMatch: String,
-    pub include: Option<SelectionInclude>,
-}
+use page::PageRequest;
 
-impl Selection {
-    fn to_json(&self) -> String {
-        let selection_type = format!("{:?}", self.selectionType);
-        let selection_match = &self.selectionMatch;
-        if let Some(include) = self.include.as_ref() {
-            let selection_include = format!("{:?}", include);
-            format!(
-                "{{\"selectionType\":\"{selection_type}\",\"selectionMatch\":\"{selection_match}\",\"{selection_include}\":true}}"
-            )
-        } else {
-            format!(
-                "{{\"selectionType\":\"{selection_type}\",\"selectionMatch\":\"{selection_match}\"}}"
-            )
-        }
-    }
-}
-
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize)]
 pub struct Status {
     pub code: i32,
     pub message: String,
@@ -88,24 +76,32 @@ pub struct GetThermostatSummaryResponse {
     pub status: Status,
 }
 
-impl Into<GetThermostatSummaryResponse> for GetThermostatSummaryResponseJson {
-    fn into(self) -> GetThermostatSummaryResponse {
-        GetThermostatSummaryResponse {
-            revisionList: self
-                .revisionList
-                .iter()
-                .map(|s| CSVRevisionValues::from_str(s).expect("Failed to make a CSV thing!"))
-                .collect(),
-            thermostatCount: self.thermostatCount,
-            statusList: self.statusList,
-            status: self.status,
-        }
+impl TryFrom<GetThermostatSummaryResponseJson> for GetThermostatSummaryResponse {
+    type Error = EcobeeError;
+
+    fn try_from(json: GetThermostatSummaryResponseJson) -> Result<Self, Self::Error> {
+        let revisionList = json
+            .revisionList
+            .iter()
+            .map(|s| {
+                CSVRevisionValues::from_str(s).map_err(|_| EcobeeError::Deserialize {
+                    source: serde::de::Error::custom(format!("malformed revision entry {s:?}")),
+                    body: s.clone(),
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(GetThermostatSummaryResponse {
+            revisionList,
+            thermostatCount: json.thermostatCount,
+            statusList: json.statusList,
+            status: json.status,
+        })
     }
 }
 
 #[derive(Debug)]
 pub struct CSVRevisionValues {
-    pub thermostat_identifier: String,
+    pub thermostat_identifier: ThermostatId,
     pub thermostat_name: String,
     pub connected: bool,
     pub thermostat_revision: String,
@@ -121,7 +117,7 @@ impl FromStr for CSVRevisionValues {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let mut splits = s.split(':');
         Ok(Self {
-            thermostat_identifier: splits.next().ok_or(())?.to_string(),
+            thermostat_identifier: splits.next().ok_or(())?.parse().map_err(|_| ())?,
             thermostat_name: splits.next().ok_or(())?.to_string(),
             connected: match splits.next().ok_or(())? {
                 "true" => true,
@@ -137,54 +133,27 @@ impl FromStr for CSVRevisionValues {
 }
 
 #[derive(Debug, Serialize)]
-pub struct GetRuntimeReportJson {
-    pub selection: String,
-    pub startDate: String,
-    pub startInterval: i32,
-    pub endDate: String,
-    pub endInterval: i32,
-    pub columns: String,
-    pub includeSensors: bool,
-}
-
-#[derive(Debug)]
 pub struct GetRuntimeReport {
     pub selection: Selection,
     pub startDate: String,
     pub startInterval: i32,
     pub endDate: String,
     pub endInterval: i32,
-    pub columns: String,
+    /// Between one and [`MAX_RUNTIME_COLUMNS`] columns.
+    #[serde(serialize_with = "runtime_report::serialize_columns")]
+    pub columns: Vec<RuntimeColumn>,
     pub includeSensors: bool,
 }
 
-impl Into<GetRuntimeReportJson> for GetRuntimeReport {
-    fn into(self) -> GetRuntimeReportJson {
-        GetRuntimeReportJson {
-            selection: self.selection.to_json(),
-            startDate: self.startDate,
-            startInterval: self.startInterval,
-            endDate: self.endDate,
-            endInterval: self.endInterval,
-            columns: self.columns,
-            includeSensors: self.includeSensors,
-        }
-    }
-}
-
 impl Default for GetRuntimeReport {
     fn default() -> Self {
         Self {
-            selection: Selection {
-                selectionType: SelectionType::thermostats,
-                selectionMatch: "".to_string(),
-                include: None,
-            },
+            selection: Selection::empty(),
             startDate: "".to_string(),
             startInterval: 0,
             endDate: "".to_string(),
             endInterval: 287,
-            columns: "".to_string(),
+            columns: Vec::new(),
             includeSensors: false,
         }
     }
@@ -224,54 +193,51 @@ pub struct GetRuntimeReportResponse {
     pub sensorList: Vec<RuntimeSensorReport>,
 }
 
-#[derive(Debug, Serialize)]
-pub struct Settings {
-    pub hvacMode: Option<String>,
+#[derive(Debug, Deserialize)]
+pub struct GetThermostatsResponse {
+    /// Which page of the results this is.  EMS accounts get 25 thermostats per page.
+    pub page: Option<Page>,
+    pub thermostatList: Vec<Thermostat>,
+    pub status: Status,
 }
 
 #[derive(Debug, Serialize)]
-pub struct Thermostat {
-    /// TODO: the spec says this is required but I suspect it might not be.
-    pub identifier: String,
-    pub settings: Option<Settings>,
+pub struct UpdateThermostat {
+    pub selection: Selection,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thermostat: Option<Thermostat>,
+    /// Run after the thermostat properties are written, in order.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub functions: Vec<Function>,
 }
 
+/// How sending a message to one thermostat went.  See [`Ecobee::send_message`].
 #[derive(Debug)]
-pub struct Function {}
-impl Serialize for Function {
-    fn serialize<S>(&self, _serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: Serializer,
-    {
-        todo!("Thermostat functions are not yet implemented.")
-    }
+pub struct MessageDelivery {
+    pub thermostat: ThermostatId,
+    pub result: Result<StatusOnlyResponse, EcobeeError>,
 }
 
-#[derive(Debug)]
-pub struct UpdateThermostat {
-    pub selection: Selection,
-    pub thermostat: Option<Thermostat>,
-    // pub functions: Option<Vec<Function>>,
+fn vacation_events(thermostat: Thermostat) -> Vec<Event> {
+    thermostat
+        .events
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|event| event.r#type.as_deref() == Some("vacation"))
+        .collect()
 }
 
-// TODO: These Options for serialization just place '"functions":null' instead of excluding.
-// There is no way to make serde_json not output null that I can find.
-
+/// Request body of the endpoints that take nothing but a selection.
 #[derive(Debug, Serialize)]
-pub struct UpdateThermostatJson {
-    pub selection: String,
-    pub thermostat: Option<Thermostat>,
-    // pub functions: Option<Vec<Function>>,
+struct SelectionBody<'a> {
+    selection: &'a Selection,
 }
 
-impl Into<UpdateThermostatJson> for UpdateThermostat {
-    fn into(self) -> UpdateThermostatJson {
-        UpdateThermostatJson {
-            selection: self.selection.to_json(),
-            thermostat: self.thermostat,
-            // functions: self.functions,
-        }
-    }
+#[derive(Debug, Serialize)]
+struct GetThermostatsBody<'a> {
+    selection: &'a Selection,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    page: Option<PageRequest>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -279,126 +245,465 @@ pub struct StatusOnlyResponse {
     pub status: Status,
 }
 
-#[derive(Debug, Deserialize)]
-pub struct RefreshTokenResponse {
-    pub access_token: String,
-    pub token_type: String,
-    pub refresh_token: String,
-    pub expires_in: i32,
-    pub scope: String,
+impl fmt::Debug for Ecobee {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Ecobee")
+            .field("api_key", &self.api_key)
+            .field("auth", &self.auth)
+            .field("refresh", &self.refresh)
+            .field("scope", &self.scope)
+            .field("token_store", &self.token_store.is_some())
+            .field("token_issued_at", &self.token_issued_at)
+            .field("token_expires_at", &self.token_expires_at)
+            .field("refresh_margin", &self.refresh_margin)
+            .finish()
+    }
+}
+
+const API_URL: &str = "https://api.ecobee.com/1";
+
+/// How long before the access token expires that it gets refreshed, unless configured otherwise.
+pub const DEFAULT_REFRESH_MARGIN_SECONDS: i64 = 5 * 60;
+
+/// The GET endpoints take their JSON in a `body` query parameter, the POST ones in the request body.
+#[derive(Debug, Clone, Copy)]
+enum Method {
+    Get,
+    Post,
 }
 
 pub struct Ecobee {
-    pub api_key: String,
-    pub auth: String,
-    pub refresh: String,
+    api_key: Secret,
+    auth: Secret,
+    refresh: Secret,
+    scope: Option<Scope>,
+    token_store: Option<Box<dyn TokenStore>>,
+    token_issued_at: Option<DateTime<Utc>>,
+    token_expires_at: Option<DateTime<Utc>>,
+    refresh_margin: Duration,
+}
+
+fn to_json(value: &impl Serialize) -> Result<String, EcobeeError> {
+    serde_json::to_string(value)
+        .map_err(|e| EcobeeError::Validation(format!("failed to serialize request: {e}")))
+}
+
+fn parse_body<T: DeserializeOwned>(body: String) -> Result<T, EcobeeError> {
+    serde_json::from_str(&body).map_err(|source| EcobeeError::Deserialize { source, body })
+}
+
+/// Non-success responses from the API carry a `Status` object explaining what went wrong.
+fn status_error(http_status: u16, body: String) -> EcobeeError {
+    match serde_json::from_str::<StatusOnlyResponse>(&body) {
+        Ok(response) => EcobeeError::from_status(http_status, response.status),
+        Err(source) => EcobeeError::Deserialize { source, body },
+    }
 }
 
 impl Ecobee {
-    pub fn refresh_key(&mut self) {
-        let current_refresh = &self.refresh;
-        let api_key = &self.api_key;
-        let url = "https://api.ecobee.com/token";
-        let data = format!("grant_type=refresh_token&code={current_refresh}&client_id={api_key}");
-        dbg!(&data);
-        let request = ureq::post(&url)
-            .set("Content-Type", "application/x-www-form-urlencoded")
-            .send_string(&data);
-        let request = request.expect("Failed to request a refresh token!");
-        let response = request
-            .into_string()
-            .expect("Failed to get body from update_thermostat request");
-        let response: RefreshTokenResponse = serde_json::from_str(&response)
-            .expect("Failed to parse a token refresh request body into a RefreshTokenResponse");
+    /// Build a client from tokens obtained elsewhere.  The granted scope and the token expiry are
+    /// unknown until the first refresh unless they are supplied with [`Ecobee::with_scope`] and
+    /// [`Ecobee::with_token_expiry`].
+    pub fn new(
+        api_key: impl Into<Secret>,
+        auth: impl Into<Secret>,
+        refresh: impl Into<Secret>,
+    ) -> Self {
+        Ecobee {
+            api_key: api_key.into(),
+            auth: auth.into(),
+            refresh: refresh.into(),
+            scope: None,
+            token_store: None,
+            token_issued_at: None,
+            token_expires_at: None,
+            refresh_margin: Duration::seconds(DEFAULT_REFRESH_MARGIN_SECONDS),
+        }
+    }
+    pub fn with_scope(mut self, scope: Scope) -> Self {
+        self.scope = Some(scope);
+        self
+    }
+    pub fn with_token_expiry(mut self, expires_at: DateTime<Utc>) -> Self {
+        self.token_expires_at = Some(expires_at);
+        self
+    }
+    /// Refresh the access token once it is within `margin` of expiring instead of waiting for a
+    /// request to be rejected.
+    pub fn with_refresh_margin(mut self, margin: Duration) -> Self {
+        self.refresh_margin = margin;
+        self
+    }
+    /// Build a client from the tokens last saved to `store`, and keep saving to it.
+    pub fn from_store(
+        api_key: impl Into<Secret>,
+        store: impl TokenStore + 'static,
+    ) -> Result<Self, EcobeeError> {
+        let tokens = store.load()?.ok_or_else(|| {
+            EcobeeError::Validation("the token store does not hold any tokens yet".to_string())
+        })?;
+        let mut ecobee = Ecobee::new(api_key, tokens.access_token, tokens.refresh_token);
+        ecobee.scope = tokens.scope;
+        ecobee.token_issued_at = tokens.issued_at;
+        ecobee.token_expires_at = tokens.expires_at;
+        ecobee.token_store = Some(Box::new(store));
+        Ok(ecobee)
+    }
+    /// Save the current tokens to `store` now and again every time they are refreshed.
+    pub fn with_token_store(
+        mut self,
+        store: impl TokenStore + 'static,
+    ) -> Result<Self, EcobeeError> {
+        store.save(&self.stored_tokens())?;
+        self.token_store = Some(Box::new(store));
+        Ok(self)
+    }
+    fn from_token_response(api_key: impl Into<Secret>, response: RefreshTokenResponse) -> Self {
+        let mut ecobee = Ecobee::new(api_key, "", "");
+        ecobee.apply_token_response(response, Utc::now());
+        ecobee
+    }
+    fn apply_token_response(&mut self, response: RefreshTokenResponse, issued_at: DateTime<Utc>) {
+        self.scope = Scope::from_granted(&response.scope).or(self.scope);
         self.auth = response.access_token;
         self.refresh = response.refresh_token;
+        self.token_issued_at = Some(issued_at);
+        self.token_expires_at = Some(issued_at + Duration::seconds(response.expires_in.into()));
     }
-    pub fn get_thermostat_summary(&mut self, selection: Selection) -> GetThermostatSummaryResponse {
-        let auth = &self.auth;
-        let selection_json = selection.to_json();
-        let url = format!("https://api.ecobee.com/1/thermostatSummary?format=json&body={{\"selection\":{selection_json}}}");
-        dbg!(&url);
-        let request = ureq::get(&url)
-            .set("Content-Type", "text/json")
-            .set("Authorization", &format!("Bearer {auth}"))
-            .call();
-        match request {
-            Ok(request) => {
-                let j: GetThermostatSummaryResponseJson = serde_json::from_str(
-                    &request
-                        .into_string()
-                        .expect("Failed to get body from get_thermostat_summary request"),
-                )
-                .expect("Failed to deserialize body from get_thermostat_summary request");
-                return j.into();
-            }
-            // TODO: Have the caller refresh the token if needed.
-            // Return a result with a possible error enum of RefreshNeeded or something
-            Err(e) => match e {
-                Error::Status(_code, response) => {
-                    let response = response
-                        .into_string()
-                        .expect("Could not read response from a non-okay server response.");
-                    let status: StatusOnlyResponse = serde_json::from_str(&response)
-                        .expect("non-okay server response was not a StatusOnlyResponse!");
-                    if status.status.code == 14 {
-                        self.refresh_key();
-                        return self.get_thermostat_summary(selection);
-                    } else {
-                        panic!("Unrecoverable response from server: {}", response);
-                    }
-                }
-                Error::Transport(e) => {
-                    panic!("{}", e);
-                }
-            },
+    fn stored_tokens(&self) -> StoredTokens {
+        StoredTokens {
+            access_token: self.auth.clone(),
+            refresh_token: self.refresh.clone(),
+            scope: self.scope,
+            issued_at: self.token_issued_at,
+            expires_at: self.token_expires_at,
+        }
+    }
+    pub fn api_key(&self) -> &Secret {
+        &self.api_key
+    }
+    pub fn access_token(&self) -> &Secret {
+        &self.auth
+    }
+    pub fn refresh_token(&self) -> &Secret {
+        &self.refresh
+    }
+    /// The scope the current tokens were granted, if known.
+    pub fn scope(&self) -> Option<Scope> {
+        self.scope
+    }
+    /// When the current access token was handed out, if known.
+    pub fn token_issued_at(&self) -> Option<DateTime<Utc>> {
+        self.token_issued_at
+    }
+    /// When the current access token stops working, if known.
+    pub fn token_expires_at(&self) -> Option<DateTime<Utc>> {
+        self.token_expires_at
+    }
+    /// Whether the access token is known to be expired, or close enough to it that a request
+    /// made at `now` should not count on it.
+    fn token_needs_refresh(&self, now: DateTime<Utc>) -> bool {
+        match self.token_expires_at {
+            Some(expires_at) => now + self.refresh_margin >= expires_at,
+            None => false,
         }
     }
-    pub fn get_runtime_report(&self, data: GetRuntimeReport) -> GetRuntimeReportResponse {
-        let auth = &self.auth;
-        let data: GetRuntimeReportJson = data.into();
-        let data = serde_json::to_string(&data)
-            .expect("Failed to serialize UpdateThermostat object!")
-            .replace("\\\"", "\"")
-            .replace(r#""selection":"{""#, r#""selection":{""#) // Fix the fact that selection has been serialized into a string.
-            .replace(r#"":true}""#, r#"":true}"#) // Fix the fact that selection has been serialized into a string.
-            .replace(r#""}""#, r#""}"#); // TODO: I NEED a better solution to this.
-
-        let request = ureq::get(&format!(
-            "https://api.ecobee.com/1/runtimeReport?format=json&body={data}"
-        ))
-        .set("Content-Type", "text/json")
-        .set("Authorization", &format!("Bearer {auth}"))
-        .call()
-        .expect("Failed to build get_runtime_report request!");
-        serde_json::from_str(
-            &request
-                .into_string()
-                .expect("Failed to get body from get_runtime_report request"),
+    /// Refuse calls that modify thermostats when the tokens are known to be read-only.
+    fn require_write(&self, call: &'static str) -> Result<(), EcobeeError> {
+        match self.scope {
+            Some(granted) if !granted.allows_write() => Err(EcobeeError::InsufficientScope {
+                call,
+                required: Scope::smartWrite,
+                granted,
+            }),
+            _ => Ok(()),
+        }
+    }
+    /// Swap the refresh token for a fresh pair of tokens, saving them to the token store if there
+    /// is one.
+    ///
+    /// A refresh token that ecobee no longer accepts comes back as [`EcobeeError::Token`], see
+    /// [`TokenError::requires_reauthorization`].
+    pub fn refresh_key(&mut self) -> Result<(), EcobeeError> {
+        let response = auth::refresh_tokens(self.api_key.expose(), self.refresh.expose())?;
+        self.apply_token_response(response, Utc::now());
+        match &self.token_store {
+            Some(store) => store.save(&self.stored_tokens()),
+            None => Ok(()),
+        }
+    }
+    pub fn get_thermostat_summary(
+        &mut self,
+        selection: Selection,
+    ) -> Result<GetThermostatSummaryResponse, EcobeeError> {
+        let response: GetThermostatSummaryResponseJson = self.send(
+            Method::Get,
+            "thermostatSummary",
+            &selection,
+            &SelectionBody {
+                selection: &selection,
+            },
+        )?;
+        GetThermostatSummaryResponse::try_from(response)
+    }
+    /// The first page of thermostats picked by `selection`, with the sub-objects its include flags
+    /// ask for.  Use [`Ecobee::thermostats_iter`] to walk every page.
+    pub fn get_thermostats(
+        &mut self,
+        selection: Selection,
+    ) -> Result<GetThermostatsResponse, EcobeeError> {
+        self.get_thermostats_page(&selection, None)
+    }
+
+    /// The vacation events scheduled on one thermostat, running or not.  Create and delete them
+    /// with [`Function::createVacation`] and [`Function::deleteVacation`].
+    pub fn vacations(&mut self, thermostat_id: &ThermostatId) -> Result<Vec<Event>, EcobeeError> {
+        let selection = Selection::thermostats(std::slice::from_ref(thermostat_id))?
+            .with(SelectionInclude::includeEvents);
+        // ecobee answers an unknown identifier with an error status, so an empty list is the only
+        // way to get here without the thermostat.
+        let response = self.get_thermostats(selection)?;
+        Ok(response
+            .thermostatList
+            .into_iter()
+            .filter(|thermostat| &thermostat.identifier == thermostat_id)
+            .flat_map(vacation_events)
+            .collect())
+    }
+
+    /// Every thermostat picked by `selection`, requesting page after page as needed.
+    pub fn thermostats_iter(&mut self, selection: Selection) -> ThermostatsIter<'_> {
+        ThermostatsIter::new(self, selection)
+    }
+
+    pub(crate) fn get_thermostats_page(
+        &mut self,
+        selection: &Selection,
+        page: Option<i32>,
+    ) -> Result<GetThermostatsResponse, EcobeeError> {
+        self.send(
+            Method::Get,
+            "thermostat",
+            selection,
+            &GetThermostatsBody {
+                selection,
+                page: page.map(|page| PageRequest { page }),
+            },
         )
-        .expect("Failed to deserialize body from get_runtime_report request")
-    }
-    pub fn update_thermostat(&self, data: UpdateThermostat) -> StatusOnlyResponse {
-        let auth = &self.auth;
-        let data: UpdateThermostatJson = data.into();
-        let data = serde_json::to_string(&data)
-            .expect("Failed to serialize UpdateThermostat object!")
-            .replace("\\\"", "\"")
-            .replace(r#""selection":"{""#, r#""selection":{""#) // Fix the fact that selection has been serialized into a string.
-            .replace(r#"":true}""#, r#"":true}"#) // Fix the fact that selection has been serialized into a string.
-            .replace(r#""}""#, r#""}"#); // TODO: I NEED a better solution to this.
-        dbg!(&data);
-        let request = ureq::post("https://api.ecobee.com/1/thermostat?format=json")
-            .set("content-type", "application/json")
-            .set("Authorization", &format!("Bearer {auth}"))
-            .send_string(&urlencoding::encode(&data).into_owned())
-            .expect("Failed to build update_thermostat request!");
-        let response = request
-            .into_string()
-            .expect("Failed to get body from update_thermostat request");
-        dbg!(&response);
-        serde_json::from_str(&response)
-            .expect("Failed to deserialize body from update_thermostat request")
+    }
+    pub fn get_runtime_report(
+        &mut self,
+        data: GetRuntimeReport,
+    ) -> Result<GetRuntimeReportResponse, EcobeeError> {
+        if data.columns.is_empty() || data.columns.len() > MAX_RUNTIME_COLUMNS {
+            return Err(EcobeeError::Validation(format!(
+                "a runtime report needs between 1 and {MAX_RUNTIME_COLUMNS} columns, got {}",
+                data.columns.len()
+            )));
+        }
+        if data.selection.selection_type() != SelectionType::thermostats
+            || data.selection.thermostat_ids().is_empty()
+        {
+            return Err(EcobeeError::Validation(
+                "a runtime report needs a selection of one or more thermostats".to_string(),
+            ));
+        }
+        self.send(Method::Get, "runtimeReport", &data.selection, &data)
+    }
+    pub fn update_thermostat(
+        &mut self,
+        data: UpdateThermostat,
+    ) -> Result<StatusOnlyResponse, EcobeeError> {
+        self.require_write("update_thermostat")?;
+        if data.thermostat.is_none() && data.functions.is_empty() {
+            return Err(EcobeeError::Validation(
+                "an update needs a thermostat object, functions, or both".to_string(),
+            ));
+        }
+        self.send(Method::Post, "thermostat", &data.selection, &data)
+    }
+
+    /// Replace a thermostat's program with `program`, typically one fetched with
+    /// [`SelectionInclude::includeProgram`] and edited since.
+    pub fn set_program(
+        &mut self,
+        thermostat_id: &ThermostatId,
+        program: Program,
+    ) -> Result<StatusOnlyResponse, EcobeeError> {
+        program.validate()?;
+        self.update_thermostat(UpdateThermostat {
+            selection: Selection::thermostats(std::slice::from_ref(thermostat_id))?,
+            thermostat: Some(Thermostat {
+                program: Some(program),
+                ..Thermostat::new(thermostat_id.clone())
+            }),
+            functions: Vec::new(),
+        })
+    }
+
+    /// Bring a thermostat's program in line with `file`, sending the update only when something
+    /// differs.  ecobee only takes whole programs, so what is sent is the live program with just
+    /// the reported changes made to it.
+    pub fn apply_schedule(
+        &mut self,
+        thermostat_id: &ThermostatId,
+        file: &ScheduleFile,
+    ) -> Result<Vec<ScheduleChange>, EcobeeError> {
+        let selection = Selection::thermostats(std::slice::from_ref(thermostat_id))?
+            .with(SelectionInclude::includeProgram);
+        let thermostat = self
+            .get_thermostats(selection)?
+            .thermostatList
+            .into_iter()
+            .find(|thermostat| &thermostat.identifier == thermostat_id)
+            .ok_or_else(|| {
+                EcobeeError::Validation(format!(
+                    "thermostat {thermostat_id} was not in the response"
+                ))
+            })?;
+        let mut program = thermostat.program.ok_or_else(|| {
+            EcobeeError::Validation(format!(
+                "thermostat {thermostat_id} came back without a program"
+            ))
+        })?;
+        let changes = file.apply_to(&mut program)?;
+        if !changes.is_empty() {
+            self.set_program(thermostat_id, program)?;
+        }
+        Ok(changes)
+    }
+
+    /// Shows `text` on the screen of every thermostat in `selection`.
+    ///
+    /// Each thermostat gets its own request, so one that is offline or refuses the message does
+    /// not hide whether the others got it.  The outer error only covers failing to validate the
+    /// message or to list the selected thermostats.
+    pub fn send_message(
+        &mut self,
+        selection: Selection,
+        text: &str,
+    ) -> Result<Vec<MessageDelivery>, EcobeeError> {
+        self.require_write("send_message")?;
+        let message = SendMessage::new(text)?;
+        let summary = self.get_thermostat_summary(selection)?;
+        Ok(summary
+            .revisionList
+            .into_iter()
+            .map(|revision| {
+                let thermostat_id = revision.thermostat_identifier;
+                let result = Selection::thermostats(std::slice::from_ref(&thermostat_id)).and_then(
+                    |selection| {
+                        self.update_thermostat(UpdateThermostat {
+                            selection,
+                            thermostat: None,
+                            functions: vec![Function::sendMessage(message.clone())],
+                        })
+                    },
+                );
+                MessageDelivery {
+                    thermostat: thermostat_id,
+                    result,
+                }
+            })
+            .collect())
+    }
+
+    /// Every API call goes through here so that an access token about to expire is refreshed up
+    /// front, and one that was rejected anyway is refreshed and the request replayed exactly once,
+    /// no matter which endpoint noticed it.
+    fn send<T: DeserializeOwned>(
+        &mut self,
+        method: Method,
+        endpoint: &'static str,
+        selection: &Selection,
+        body: &impl Serialize,
+    ) -> Result<T, EcobeeError> {
+        let selection = to_json(selection)?;
+        let body = &to_json(body)?;
+        if self.token_needs_refresh(Utc::now()) {
+            tracing::debug!(
+                expires_at = ?self.token_expires_at,
+                "access token is about to expire, refreshing before the request"
+            );
+            self.refresh_key()?;
+        }
+        match self.send_once(method, endpoint, &selection, body) {
+            Err(EcobeeError::AuthExpired(_)) => {
+                tracing::info!(endpoint, "access token was rejected as expired, refreshing");
+                self.refresh_key()?;
+                self.send_once(method, endpoint, &selection, body)
+                    .map_err(|e| EcobeeError::RetryFailed {
+                        endpoint,
+                        source: Box::new(e),
+                    })
+            }
+            result => result,
+        }
+    }
+
+    fn send_once<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        endpoint: &str,
+        selection: &str,
+        body: &str,
+    ) -> Result<T, EcobeeError> {
+        let span = tracing::info_span!(
+            "ecobee_request",
+            endpoint,
+            method = ?method,
+            selection,
+            latency_ms = tracing::field::Empty,
+            http_status = tracing::field::Empty,
+            ecobee_status = tracing::field::Empty,
+        );
+        let _entered = span.enter();
+        #[cfg(feature = "trace-bodies")]
+        tracing::trace!(body = %secret::redact_json(body, secret::SECRET_KEYS), "request body");
+
+        let started = Instant::now();
+        let auth = self.auth.expose();
+        let request = match method {
+            Method::Get => {
+                let body = urlencoding::encode(body);
+                ureq::get(&format!("{API_URL}/{endpoint}?format=json&body={body}"))
+                    .set("Content-Type", "text/json")
+                    .set("Authorization", &format!("Bearer {auth}"))
+                    .call()
+            }
+            Method::Post => ureq::post(&format!("{API_URL}/{endpoint}?format=json"))
+                .set("Content-Type", "application/json")
+                .set("Authorization", &format!("Bearer {auth}"))
+                .send_string(&urlencoding::encode(body)),
+        };
+        let (http_status, response) = match request {
+            Ok(response) => (response.status(), response.into_string()),
+            Err(ureq::Error::Status(http_status, response)) => {
+                (http_status, response.into_string())
+            }
+            Err(ureq::Error::Transport(e)) => {
+                span.record("latency_ms", started.elapsed().as_millis() as u64);
+                let e = EcobeeError::from(e);
+                tracing::warn!(error = %e, "request did not get a response");
+                return Err(e);
+            }
+        };
+        span.record("latency_ms", started.elapsed().as_millis() as u64);
+        span.record("http_status", http_status);
+        let response = response?;
+        #[cfg(feature = "trace-bodies")]
+        tracing::trace!(body = %secret::redact_json(&response, secret::SECRET_KEYS), "response body");
+
+        if let Ok(status) = serde_json::from_str::<StatusOnlyResponse>(&response) {
+            span.record("ecobee_status", status.status.code);
+        }
+        if http_status < 300 {
+            parse_body(response)
+        } else {
+            let error = status_error(http_status, response);
+            tracing::warn!(%error, "request failed");
+            Err(error)
+        }
     }
 }
 
@@ -407,18 +712,289 @@ mod tests {
     use crate::*;
 
     #[test]
+    #[ignore = "talks to the live ecobee API; needs ECOBEE_KEY, ECOBEE_AUTH and ECOBEE_REFRESH"]
     fn thermostat_summary() {
-        let mut bee = Ecobee {
-            api_key: std::env::var("ECOBEE_KEY").expect("ECOBEE_KEY must be est to run tests"),
-            auth: std::env::var("ECOBEE_AUTH").expect("ECOBEE_AUTH must be set to run tests"),
-            refresh: std::env::var("ECOBEE_REFRESH")
-                .expect("ECOBEE_REFRESH must be est to run tests"),
+        let mut bee = Ecobee::new(
+            std::env::var("ECOBEE_KEY").expect("ECOBEE_KEY must be est to run tests"),
+            std::env::var("ECOBEE_AUTH").expect("ECOBEE_AUTH must be set to run tests"),
+            std::env::var("ECOBEE_REFRESH").expect("ECOBEE_REFRESH must be est to run tests"),
+        );
+        let ret = bee
+            .get_thermostat_summary(Selection::registered().with(SelectionInclude::includeDevice));
+        let summary = ret.expect("thermostatSummary request failed");
+        assert_eq!(summary.status.code, 0);
+        assert_eq!(summary.revisionList.len(), summary.thermostatCount as usize);
+    }
+
+    #[test]
+    fn thermostat_summary_body() {
+        let selection = Selection::registered()
+            .with(SelectionInclude::includeSettings)
+            .with(SelectionInclude::includeRuntime)
+            .with(SelectionInclude::includeEquipmentStatus)
+            .with(SelectionInclude::includeSensors)
+            .with(SelectionInclude::includeRuntime);
+        assert_eq!(
+            to_json(&SelectionBody {
+                selection: &selection
+            })
+            .unwrap(),
+            r#"{"selection":{"selectionType":"registered","selectionMatch":"","includeRuntime":true,"includeSettings":true,"includeEquipmentStatus":true,"includeSensors":true}}"#
+        );
+    }
+
+    #[test]
+    fn thermostats_page_body() {
+        let selection = Selection::registered();
+        assert_eq!(
+            to_json(&GetThermostatsBody {
+                selection: &selection,
+                page: Some(PageRequest { page: 2 }),
+            })
+            .unwrap(),
+            r#"{"selection":{"selectionType":"registered","selectionMatch":""},"page":{"page":2}}"#
+        );
+    }
+
+    #[test]
+    fn runtime_report_body() {
+        let report = GetRuntimeReport {
+            selection: Selection::thermostats(&["522697894617".parse().unwrap()]).unwrap(),
+            startDate: "2022-01-15".to_string(),
+            endDate: "2022-01-15".to_string(),
+            columns: vec![
+                RuntimeColumn::zoneHvacMode,
+                RuntimeColumn::zoneCalendarEvent,
+            ],
+            includeSensors: true,
+            ..Default::default()
         };
-        let ret = bee.get_thermostat_summary(Selection {
-            selectionType: SelectionType::registered,
-            selectionMatch: "".to_string(),
-            include: Some(SelectionInclude::includeDevice),
+        assert_eq!(
+            to_json(&report).unwrap(),
+            r#"{"selection":{"selectionType":"thermostats","selectionMatch":"522697894617"},"startDate":"2022-01-15","startInterval":0,"endDate":"2022-01-15","endInterval":287,"columns":"zoneHvacMode,zoneCalendarEvent","includeSensors":true}"#
+        );
+    }
+
+    #[test]
+    fn update_thermostat_body() {
+        let update = UpdateThermostat {
+            selection: Selection::registered(),
+            thermostat: Some(Thermostat {
+                settings: Some(Settings {
+                    hvacMode: Some(HvacMode::off),
+                    ..Settings::default()
+                }),
+                ..Thermostat::new("522697894617".parse().unwrap())
+            }),
+            functions: Vec::new(),
+        };
+        assert_eq!(
+            to_json(&update).unwrap(),
+            r#"{"selection":{"selectionType":"registered","selectionMatch":""},"thermostat":{"identifier":"522697894617","settings":{"hvacMode":"off"}}}"#
+        );
+
+        let update = UpdateThermostat {
+            thermostat: None,
+            functions: vec![Function::setHold(
+                SetHold::climate("away", HoldType::indefinite).unwrap(),
+            )],
+            ..update
+        };
+        assert_eq!(
+            to_json(&update).unwrap(),
+            r#"{"selection":{"selectionType":"registered","selectionMatch":""},"functions":[{"type":"setHold","params":{"holdClimateRef":"away","holdType":"indefinite"}}]}"#
+        );
+    }
+
+    #[test]
+    fn vacations_are_filtered_from_events() {
+        let thermostat: Thermostat = serde_json::from_str(
+            r#"{"identifier":"522697894617","events":[
+                {"type":"hold","name":"auto","running":true},
+                {"type":"vacation","name":"Cottage","running":false,"startDate":"2022-07-01"}
+            ]}"#,
+        )
+        .unwrap();
+        let vacations = vacation_events(thermostat);
+        assert_eq!(vacations.len(), 1);
+        assert_eq!(vacations[0].name.as_deref(), Some("Cottage"));
+        assert_eq!(
+            vacation_events(Thermostat::new("1".parse().unwrap())),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn status_error_sorts_ecobee_codes() {
+        let expired = r#"{"status":{"code":14,"message":"Authentication token has expired."}}"#;
+        assert!(matches!(
+            status_error(500, expired.to_string()),
+            EcobeeError::AuthExpired(Status { code: 14, .. })
+        ));
+        let invalid = r#"{"status":{"code":16,"message":"Authentication token is invalid."}}"#;
+        assert!(matches!(
+            status_error(500, invalid.to_string()),
+            EcobeeError::InvalidToken(Status { code: 16, .. })
+        ));
+        let other = r#"{"status":{"code":3,"message":"Processing error."}}"#;
+        assert!(matches!(
+            status_error(500, other.to_string()),
+            EcobeeError::Status {
+                http_status: 500,
+                status: Status { code: 3, .. }
+            }
+        ));
+    }
+
+    #[test]
+    fn status_error_keeps_unparseable_body() {
+        match status_error(502, "<html>Bad Gateway</html>".to_string()) {
+            EcobeeError::Deserialize { body, .. } => assert_eq!(body, "<html>Bad Gateway</html>"),
+            e => panic!("unexpected error {:?}", e),
+        }
+    }
+
+    #[test]
+    fn empty_update_is_refused() {
+        let mut bee = Ecobee::new("key", "auth", "refresh");
+        let result = bee.update_thermostat(UpdateThermostat {
+            selection: Selection::registered(),
+            thermostat: None,
+            functions: Vec::new(),
         });
-        dbg!(ret);
+        assert!(matches!(result, Err(EcobeeError::Validation(_))));
+    }
+
+    #[test]
+    fn long_message_is_refused_before_sending() {
+        let mut bee = Ecobee::new("key", "auth", "refresh");
+        let text = "x".repeat(MAX_MESSAGE_LENGTH + 1);
+        assert!(matches!(
+            bee.send_message(Selection::registered(), &text),
+            Err(EcobeeError::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn runtime_report_column_limit() {
+        let mut bee = Ecobee::new("key", "auth", "refresh");
+        let report = |columns| GetRuntimeReport {
+            selection: Selection::thermostats(&["522697894617".parse().unwrap()]).unwrap(),
+            columns,
+            ..Default::default()
+        };
+        assert!(matches!(
+            bee.get_runtime_report(report(Vec::new())),
+            Err(EcobeeError::Validation(_))
+        ));
+        let columns = vec![RuntimeColumn::fan; MAX_RUNTIME_COLUMNS + 1];
+        assert!(matches!(
+            bee.get_runtime_report(report(columns)),
+            Err(EcobeeError::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn runtime_report_needs_thermostats() {
+        let mut bee = Ecobee::new("key", "auth", "refresh");
+        for selection in [Selection::empty(), Selection::registered()] {
+            let report = GetRuntimeReport {
+                selection,
+                columns: vec![RuntimeColumn::fan],
+                ..Default::default()
+            };
+            assert!(matches!(
+                bee.get_runtime_report(report),
+                Err(EcobeeError::Validation(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn read_only_client_refuses_updates() {
+        let mut bee = Ecobee::new("key", "auth", "refresh").with_scope(Scope::smartRead);
+        let result = bee.update_thermostat(UpdateThermostat {
+            selection: Selection::registered(),
+            thermostat: None,
+            functions: Vec::new(),
+        });
+        assert!(matches!(
+            result,
+            Err(EcobeeError::InsufficientScope {
+                call: "update_thermostat",
+                granted: Scope::smartRead,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn client_from_store() {
+        let store = MemoryTokenStore::with_tokens(StoredTokens {
+            access_token: "auth".into(),
+            refresh_token: "refresh".into(),
+            scope: Some(Scope::smartRead),
+            issued_at: None,
+            expires_at: Some("2022-01-15T22:00:00Z".parse::<DateTime<Utc>>().unwrap()),
+        });
+        let bee = Ecobee::from_store("key", store).unwrap();
+        assert_eq!(bee.access_token().expose(), "auth");
+        assert_eq!(bee.refresh_token().expose(), "refresh");
+        assert_eq!(bee.scope(), Some(Scope::smartRead));
+        assert_eq!(
+            bee.token_expires_at(),
+            Some("2022-01-15T22:00:00Z".parse::<DateTime<Utc>>().unwrap())
+        );
+
+        assert!(matches!(
+            Ecobee::from_store("key", MemoryTokenStore::new()),
+            Err(EcobeeError::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn token_expiry_tracking() {
+        let issued_at = "2022-01-15T21:00:00Z".parse::<DateTime<Utc>>().unwrap();
+        let mut bee = Ecobee::new("key", "old", "old").with_refresh_margin(Duration::minutes(2));
+        assert!(!bee.token_needs_refresh(issued_at));
+        bee.apply_token_response(
+            RefreshTokenResponse {
+                access_token: "auth".into(),
+                token_type: "Bearer".to_string(),
+                refresh_token: "refresh".into(),
+                expires_in: 3600,
+                scope: "smartWrite".to_string(),
+            },
+            issued_at,
+        );
+        assert_eq!(bee.token_issued_at(), Some(issued_at));
+        assert_eq!(bee.token_expires_at(), Some(issued_at + Duration::hours(1)));
+        assert!(!bee.token_needs_refresh(issued_at + Duration::minutes(57)));
+        assert!(bee.token_needs_refresh(issued_at + Duration::minutes(58)));
+        assert!(bee.token_needs_refresh(issued_at + Duration::hours(2)));
+    }
+
+    #[test]
+    fn debug_output_hides_credentials() {
+        let bee = Ecobee::new("the-api-key", "the-access-token", "the-refresh-token");
+        let debug = format!("{bee:?}");
+        assert!(!debug.contains("the-"), "{}", debug);
+    }
+
+    #[test]
+    fn malformed_revision_is_an_error() {
+        let json = GetThermostatSummaryResponseJson {
+            revisionList: vec!["522697894617:My ecobee:maybe".to_string()],
+            thermostatCount: 1,
+            statusList: vec![],
+            status: Status {
+                code: 0,
+                message: String::new(),
+            },
+        };
+        assert!(matches!(
+            GetThermostatSummaryResponse::try_from(json),
+            Err(EcobeeError::Deserialize { .. })
+        ));
     }
 }
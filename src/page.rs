@@ -0,0 +1,91 @@
+use crate::{Ecobee, EcobeeError, Selection, Thermostat};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// Where a response sits in a paged result set.  ecobee numbers pages from 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct Page {
+    pub page: i32,
+    pub totalPages: i32,
+    pub pageSize: i32,
+    pub total: i32,
+}
+
+impl Page {
+    /// The page to ask for after this one, or `None` if this was the last.
+    pub fn next_page(&self) -> Option<i32> {
+        if self.page < self.totalPages {
+            Some(self.page + 1)
+        } else {
+            None
+        }
+    }
+}
+
+/// The `page` object of a request, asking for one particular page.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub(crate) struct PageRequest {
+    pub(crate) page: i32,
+}
+
+/// Yields every thermostat picked by a selection, fetching the next page whenever the previous one
+/// runs out.  Returned by [`Ecobee::thermostats_iter`].
+///
+/// Each page is a separate request that refreshes the access token like any other call.  After
+/// the first error the iterator yields nothing more.
+#[derive(Debug)]
+pub struct ThermostatsIter<'a> {
+    client: &'a mut Ecobee,
+    selection: Selection,
+    buffered: VecDeque<Thermostat>,
+    next_page: Option<i32>,
+}
+
+impl<'a> ThermostatsIter<'a> {
+    pub(crate) fn new(client: &'a mut Ecobee, selection: Selection) -> Self {
+        ThermostatsIter {
+            client,
+            selection,
+            buffered: VecDeque::new(),
+            next_page: Some(1),
+        }
+    }
+}
+
+impl Iterator for ThermostatsIter<'_> {
+    type Item = Result<Thermostat, EcobeeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // Loop rather than recurse so that an empty page in the middle does not end iteration.
+        loop {
+            if let Some(thermostat) = self.buffered.pop_front() {
+                return Some(Ok(thermostat));
+            }
+            let page = self.next_page.take()?;
+            match self
+                .client
+                .get_thermostats_page(&self.selection, Some(page))
+            {
+                Ok(response) => {
+                    self.next_page = response.page.as_ref().and_then(Page::next_page);
+                    self.buffered.extend(response.thermostatList);
+                }
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn next_page() {
+        let page: Page =
+            serde_json::from_str(r#"{"page":1,"totalPages":3,"pageSize":25,"total":61}"#).unwrap();
+        assert_eq!(page.next_page(), Some(2));
+        let last = Page { page: 3, ..page };
+        assert_eq!(last.next_page(), None);
+    }
+}
@@ -0,0 +1,394 @@
+//! Editing a thermostat's weekly [`Program`], and which sensors each of its climates reads,
+//! without breaking the invariants ecobee checks when it is written back.
+
+use crate::{Climate, ClimateSensor, EcobeeError, Program, RemoteSensor, Thermostat};
+use chrono::{NaiveTime, Timelike, Weekday};
+
+impl Program {
+    /// The schedule starts on Monday.
+    pub const DAYS: usize = 7;
+    /// Half hour blocks per day.
+    pub const BLOCKS_PER_DAY: usize = 48;
+
+    /// The climate with this `climateRef`, if the program has one.
+    pub fn climate(&self, climate_ref: &str) -> Option<&Climate> {
+        self.climates
+            .iter()
+            .flatten()
+            .find(|climate| climate.climateRef.as_deref() == Some(climate_ref))
+    }
+
+    pub fn climate_mut(&mut self, climate_ref: &str) -> Option<&mut Climate> {
+        self.climates
+            .iter_mut()
+            .flatten()
+            .find(|climate| climate.climateRef.as_deref() == Some(climate_ref))
+    }
+
+    /// Check that the schedule is a full week of half hour blocks and that every block names a
+    /// climate of this program.  ecobee refuses a program that fails either.
+    pub fn validate(&self) -> Result<(), EcobeeError> {
+        let schedule = self.schedule()?;
+        for day in schedule {
+            for climate_ref in day {
+                if self.climate(climate_ref).is_none() {
+                    return Err(EcobeeError::Validation(format!(
+                        "the schedule uses climate {climate_ref:?}, which the program does not have"
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn schedule(&self) -> Result<&Vec<Vec<String>>, EcobeeError> {
+        match &self.schedule {
+            Some(schedule)
+                if schedule.len() == Self::DAYS
+                    && schedule.iter().all(|day| day.len() == Self::BLOCKS_PER_DAY) =>
+            {
+                Ok(schedule)
+            }
+            _ => Err(EcobeeError::Validation(format!(
+                "a schedule needs {} days of {} blocks; fetch the thermostat with includeProgram first",
+                Self::DAYS,
+                Self::BLOCKS_PER_DAY
+            ))),
+        }
+    }
+
+    /// Run `climate_ref` on `day` from `start` until `end`.  Both must fall on the hour or half
+    /// hour, and an `end` of midnight means the end of the day.
+    pub fn set_block(
+        &mut self,
+        day: Weekday,
+        start: NaiveTime,
+        end: NaiveTime,
+        climate_ref: &str,
+    ) -> Result<(), EcobeeError> {
+        self.schedule()?;
+        if self.climate(climate_ref).is_none() {
+            return Err(EcobeeError::Validation(format!(
+                "the program has no climate {climate_ref:?}"
+            )));
+        }
+        let first = block(start)?;
+        let last = match block(end)? {
+            0 => Self::BLOCKS_PER_DAY,
+            end => end,
+        };
+        if first >= last {
+            return Err(EcobeeError::Validation(format!(
+                "a block cannot end ({end}) before it starts ({start})"
+            )));
+        }
+        let Some(schedule) = self.schedule.as_mut() else {
+            return Err(EcobeeError::Validation(
+                "the program has no schedule".to_string(),
+            ));
+        };
+        let day = &mut schedule[day.num_days_from_monday() as usize];
+        for slot in &mut day[first..last] {
+            *slot = climate_ref.to_string();
+        }
+        Ok(())
+    }
+
+    /// Add a climate for the schedule to use.  It needs a name and a `climateRef` that no other
+    /// climate of the program already has.
+    pub fn add_climate(&mut self, climate: Climate) -> Result<(), EcobeeError> {
+        let (name, climate_ref) = match (&climate.name, &climate.climateRef) {
+            (Some(name), Some(climate_ref)) if !name.is_empty() && !climate_ref.is_empty() => {
+                (name, climate_ref)
+            }
+            _ => {
+                return Err(EcobeeError::Validation(
+                    "a climate needs a name and a climateRef".to_string(),
+                ))
+            }
+        };
+        let taken = self.climates.iter().flatten().any(|existing| {
+            existing.name.as_ref() == Some(name)
+                || existing.climateRef.as_ref() == Some(climate_ref)
+        });
+        if taken {
+            return Err(EcobeeError::Validation(format!(
+                "the program already has a climate named {name:?} or with ref {climate_ref:?}"
+            )));
+        }
+        self.climates.get_or_insert_with(Vec::new).push(climate);
+        Ok(())
+    }
+
+    /// Remove a climate that nothing refers to any more, handing it back.
+    pub fn remove_climate(&mut self, climate_ref: &str) -> Result<Climate, EcobeeError> {
+        let in_schedule = self
+            .schedule
+            .iter()
+            .flatten()
+            .flatten()
+            .any(|block| block == climate_ref);
+        if in_schedule {
+            return Err(EcobeeError::Validation(format!(
+                "climate {climate_ref:?} is still used by the schedule"
+            )));
+        }
+        if self.currentClimateRef.as_deref() == Some(climate_ref) {
+            return Err(EcobeeError::Validation(format!(
+                "climate {climate_ref:?} is the one running now"
+            )));
+        }
+        let climates = self.climates.as_mut();
+        let found = climates.and_then(|climates| {
+            let index = climates
+                .iter()
+                .position(|climate| climate.climateRef.as_deref() == Some(climate_ref))?;
+            Some(climates.remove(index))
+        });
+        found.ok_or_else(|| {
+            EcobeeError::Validation(format!("the program has no climate {climate_ref:?}"))
+        })
+    }
+}
+
+impl Thermostat {
+    /// Have the sensor with [`RemoteSensor::id`] `sensor_id` take part in a climate, so its
+    /// readings count while that climate runs.  Needs a thermostat fetched with both
+    /// `includeProgram` and `includeSensors`.
+    pub fn add_climate_sensor(
+        &mut self,
+        climate_ref: &str,
+        sensor_id: &str,
+    ) -> Result<(), EcobeeError> {
+        let participant = participant(self.remote_sensor(sensor_id)?);
+        let sensors = self
+            .program_climate(climate_ref)?
+            .sensors
+            .get_or_insert_with(Vec::new);
+        if !sensors.iter().any(|sensor| sensor.id == participant.id) {
+            sensors.push(participant);
+        }
+        Ok(())
+    }
+
+    /// Stop the sensor with [`RemoteSensor::id`] `sensor_id` from taking part in a climate.  The
+    /// last sensor of a climate cannot be removed since the thermostat would have nothing to read.
+    pub fn remove_climate_sensor(
+        &mut self,
+        climate_ref: &str,
+        sensor_id: &str,
+    ) -> Result<(), EcobeeError> {
+        let participant = participant(self.remote_sensor(sensor_id)?);
+        let Some(sensors) = self.program_climate(climate_ref)?.sensors.as_mut() else {
+            return Ok(());
+        };
+        if sensors.iter().all(|sensor| sensor.id != participant.id) {
+            return Ok(());
+        }
+        if sensors.len() == 1 {
+            return Err(EcobeeError::Validation(format!(
+                "sensor {sensor_id:?} is the last one left in climate {climate_ref:?}"
+            )));
+        }
+        sensors.retain(|sensor| sensor.id != participant.id);
+        Ok(())
+    }
+
+    fn remote_sensor(&self, sensor_id: &str) -> Result<&RemoteSensor, EcobeeError> {
+        let sensors = self.remoteSensors.as_ref().ok_or_else(|| {
+            EcobeeError::Validation(
+                "the thermostat has no sensor list; fetch it with includeSensors".to_string(),
+            )
+        })?;
+        sensors
+            .iter()
+            .find(|sensor| sensor.id.as_deref() == Some(sensor_id))
+            .ok_or_else(|| {
+                EcobeeError::Validation(format!(
+                    "sensor {sensor_id:?} does not belong to thermostat {}",
+                    self.identifier
+                ))
+            })
+    }
+
+    fn program_climate(&mut self, climate_ref: &str) -> Result<&mut Climate, EcobeeError> {
+        let program = self.program.as_mut().ok_or_else(|| {
+            EcobeeError::Validation(
+                "the thermostat has no program; fetch it with includeProgram".to_string(),
+            )
+        })?;
+        program.climate_mut(climate_ref).ok_or_else(|| {
+            EcobeeError::Validation(format!("the program has no climate {climate_ref:?}"))
+        })
+    }
+}
+
+/// How a remote sensor is listed in a climate: by its first input, which carries temperature and
+/// occupancy.
+fn participant(sensor: &RemoteSensor) -> ClimateSensor {
+    ClimateSensor {
+        id: sensor.id.as_ref().map(|id| format!("{id}:1")),
+        name: sensor.name.clone(),
+    }
+}
+
+/// The schedule block starting at `time`.
+pub(crate) fn block(time: NaiveTime) -> Result<usize, EcobeeError> {
+    if !time.minute().is_multiple_of(30) || time.second() != 0 || time.nanosecond() != 0 {
+        return Err(EcobeeError::Validation(format!(
+            "{time} is not on the hour or half hour"
+        )));
+    }
+    Ok((time.hour() * 2 + time.minute() / 30) as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn climate(name: &str, climate_ref: &str) -> Climate {
+        Climate {
+            name: Some(name.to_string()),
+            climateRef: Some(climate_ref.to_string()),
+            ..Climate::default()
+        }
+    }
+
+    fn program() -> Program {
+        Program {
+            schedule: Some(vec![
+                vec!["home".to_string(); Program::BLOCKS_PER_DAY];
+                Program::DAYS
+            ]),
+            climates: Some(vec![climate("Home", "home"), climate("Away", "away")]),
+            currentClimateRef: Some("home".to_string()),
+        }
+    }
+
+    fn time(time: &str) -> NaiveTime {
+        time.parse().unwrap()
+    }
+
+    #[test]
+    fn set_block() {
+        let mut program = program();
+        program
+            .set_block(Weekday::Tue, time("08:30:00"), time("17:00:00"), "away")
+            .unwrap();
+        program
+            .set_block(Weekday::Sun, time("23:30:00"), time("00:00:00"), "away")
+            .unwrap();
+        let schedule = program.schedule.as_ref().unwrap();
+        assert_eq!(schedule[1][16], "home");
+        assert_eq!(schedule[1][17], "away");
+        assert_eq!(schedule[1][33], "away");
+        assert_eq!(schedule[1][34], "home");
+        assert_eq!(schedule[6][47], "away");
+        assert!(program.validate().is_ok());
+
+        let mon = Weekday::Mon;
+        assert!(program
+            .set_block(mon, time("08:15:00"), time("09:00:00"), "away")
+            .is_err());
+        assert!(program
+            .set_block(mon, time("09:00:00"), time("08:00:00"), "away")
+            .is_err());
+        assert!(program
+            .set_block(mon, time("08:00:00"), time("09:00:00"), "sleep")
+            .is_err());
+        assert!(Program::default()
+            .set_block(mon, time("08:00:00"), time("09:00:00"), "home")
+            .is_err());
+    }
+
+    #[test]
+    fn add_and_remove_climates() {
+        let mut program = program();
+        program.add_climate(climate("Gym", "gym")).unwrap();
+        assert!(program.add_climate(climate("Gym", "gym2")).is_err());
+        assert!(program.add_climate(climate("Gym 2", "gym")).is_err());
+        assert!(program.add_climate(Climate::default()).is_err());
+
+        program
+            .set_block(Weekday::Sat, time("10:00:00"), time("11:00:00"), "gym")
+            .unwrap();
+        assert!(program.remove_climate("gym").is_err());
+        assert!(program.remove_climate("home").is_err());
+        assert!(program.remove_climate("missing").is_err());
+        assert_eq!(
+            program.remove_climate("away").unwrap().name.as_deref(),
+            Some("Away")
+        );
+        assert!(program.climate("away").is_none());
+
+        let mut empty = Program::default();
+        assert!(empty.remove_climate("away").is_err());
+        assert_eq!(empty.climates, None);
+    }
+
+    #[test]
+    fn validate_catches_unknown_climates() {
+        let mut program = program();
+        program.schedule.as_mut().unwrap()[3][20] = "sleep".to_string();
+        assert!(program.validate().is_err());
+        program.schedule.as_mut().unwrap()[3].pop();
+        assert!(program.validate().is_err());
+    }
+
+    fn thermostat() -> Thermostat {
+        let sensor = |id: &str, name: &str| RemoteSensor {
+            id: Some(id.to_string()),
+            name: Some(name.to_string()),
+            ..RemoteSensor::default()
+        };
+        let mut program = program();
+        program.climate_mut("home").unwrap().sensors = Some(vec![ClimateSensor {
+            id: Some("ei:0:1".to_string()),
+            name: Some("Hallway".to_string()),
+        }]);
+        Thermostat {
+            program: Some(program),
+            remoteSensors: Some(vec![sensor("ei:0", "Hallway"), sensor("rs:100", "Bedroom")]),
+            ..Thermostat::new("522697894617".parse().unwrap())
+        }
+    }
+
+    fn sensor_ids(thermostat: &Thermostat, climate_ref: &str) -> Vec<String> {
+        let program = thermostat.program.as_ref().unwrap();
+        let sensors = program
+            .climate(climate_ref)
+            .unwrap()
+            .sensors
+            .iter()
+            .flatten();
+        sensors.map(|sensor| sensor.id.clone().unwrap()).collect()
+    }
+
+    #[test]
+    fn climate_sensors() {
+        let mut thermostat = thermostat();
+        thermostat.add_climate_sensor("home", "rs:100").unwrap();
+        thermostat.add_climate_sensor("home", "rs:100").unwrap();
+        assert_eq!(sensor_ids(&thermostat, "home"), vec!["ei:0:1", "rs:100:1"]);
+
+        thermostat.remove_climate_sensor("home", "ei:0").unwrap();
+        assert_eq!(sensor_ids(&thermostat, "home"), vec!["rs:100:1"]);
+        assert!(thermostat.remove_climate_sensor("home", "rs:100").is_err());
+        assert_eq!(sensor_ids(&thermostat, "home"), vec!["rs:100:1"]);
+
+        assert!(thermostat.add_climate_sensor("home", "rs:999").is_err());
+        assert!(thermostat.add_climate_sensor("sleep", "rs:100").is_err());
+        let away = |thermostat: &Thermostat| {
+            let program = thermostat.program.as_ref().unwrap();
+            program.climate("away").unwrap().sensors.clone()
+        };
+        assert_eq!(away(&thermostat), None);
+        thermostat.remove_climate_sensor("away", "rs:100").unwrap();
+        assert_eq!(away(&thermostat), None);
+        thermostat.add_climate_sensor("away", "rs:100").unwrap();
+        assert_eq!(sensor_ids(&thermostat, "away"), vec!["rs:100:1"]);
+
+        let mut bare = Thermostat::new("522697894617".parse().unwrap());
+        assert!(bare.add_climate_sensor("home", "rs:100").is_err());
+    }
+}
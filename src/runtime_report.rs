@@ -0,0 +1,89 @@
+use serde::{Serialize, Serializer};
+
+/// ecobee refuses runtime report requests asking for more columns than this.
+pub const MAX_RUNTIME_COLUMNS: usize = 25;
+
+/// A column of a runtime report.  Runtimes are in seconds per five minute interval and
+/// temperatures in degrees Fahrenheit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub enum RuntimeColumn {
+    auxHeat1,
+    auxHeat2,
+    auxHeat3,
+    compCool1,
+    compCool2,
+    compHeat1,
+    compHeat2,
+    dehumidifier,
+    dmOffset,
+    economizer,
+    fan,
+    humidifier,
+    hvacMode,
+    outdoorHumidity,
+    outdoorTemp,
+    sky,
+    ventilator,
+    wind,
+    zoneAveTemp,
+    zoneCalendarEvent,
+    zoneClimate,
+    zoneCoolTemp,
+    zoneHeatTemp,
+    zoneHumidity,
+    zoneHumidityHigh,
+    zoneHumidityLow,
+    zoneHvacMode,
+    zoneOccupancy,
+}
+
+impl RuntimeColumn {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RuntimeColumn::auxHeat1 => "auxHeat1",
+            RuntimeColumn::auxHeat2 => "auxHeat2",
+            RuntimeColumn::auxHeat3 => "auxHeat3",
+            RuntimeColumn::compCool1 => "compCool1",
+            RuntimeColumn::compCool2 => "compCool2",
+            RuntimeColumn::compHeat1 => "compHeat1",
+            RuntimeColumn::compHeat2 => "compHeat2",
+            RuntimeColumn::dehumidifier => "dehumidifier",
+            RuntimeColumn::dmOffset => "dmOffset",
+            RuntimeColumn::economizer => "economizer",
+            RuntimeColumn::fan => "fan",
+            RuntimeColumn::humidifier => "humidifier",
+            RuntimeColumn::hvacMode => "hvacMode",
+            RuntimeColumn::outdoorHumidity => "outdoorHumidity",
+            RuntimeColumn::outdoorTemp => "outdoorTemp",
+            RuntimeColumn::sky => "sky",
+            RuntimeColumn::ventilator => "ventilator",
+            RuntimeColumn::wind => "wind",
+            RuntimeColumn::zoneAveTemp => "zoneAveTemp",
+            RuntimeColumn::zoneCalendarEvent => "zoneCalendarEvent",
+            RuntimeColumn::zoneClimate => "zoneClimate",
+            RuntimeColumn::zoneCoolTemp => "zoneCoolTemp",
+            RuntimeColumn::zoneHeatTemp => "zoneHeatTemp",
+            RuntimeColumn::zoneHumidity => "zoneHumidity",
+            RuntimeColumn::zoneHumidityHigh => "zoneHumidityHigh",
+            RuntimeColumn::zoneHumidityLow => "zoneHumidityLow",
+            RuntimeColumn::zoneHvacMode => "zoneHvacMode",
+            RuntimeColumn::zoneOccupancy => "zoneOccupancy",
+        }
+    }
+}
+
+/// ecobee takes the columns as one comma separated string.
+pub(crate) fn serialize_columns<S>(
+    columns: &[RuntimeColumn],
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    let joined = columns
+        .iter()
+        .map(RuntimeColumn::as_str)
+        .collect::<Vec<_>>()
+        .join(",");
+    serializer.serialize_str(&joined)
+}
@@ -0,0 +1,605 @@
+//! A [`Program`] as a TOML file that reads well in code review, such as
+//!
+//! ```toml
+//! schedule = [
+//!     "mon-fri 00:00-07:00 Sleep",
+//!     "mon-fri 07:00-18:00 Home",
+//!     "mon-fri 18:00-24:00 Sleep",
+//!     "sat-sun 00:00-24:00 Home",
+//! ]
+//!
+//! [[climates]]
+//! name = "Home"
+//! climateRef = "home"
+//! coolTemp = 760
+//! heatTemp = 700
+//!
+//! [[climates]]
+//! name = "Sleep"
+//! climateRef = "sleep"
+//! ```
+//!
+//! Blocks name climates by `name`, run from their start up to but not including their end, and
+//! must cover every half hour of the week exactly once.  Temperatures are in tenths of a degree
+//! Fahrenheit and are left alone when missing.
+
+use crate::program::block;
+use crate::{Climate, EcobeeError, Program};
+use chrono::{NaiveTime, Weekday};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+const DAY_NAMES: [&str; Program::DAYS] = ["mon", "tue", "wed", "thu", "fri", "sat", "sun"];
+const WEEKDAYS: [Weekday; Program::DAYS] = [
+    Weekday::Mon,
+    Weekday::Tue,
+    Weekday::Wed,
+    Weekday::Thu,
+    Weekday::Fri,
+    Weekday::Sat,
+    Weekday::Sun,
+];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScheduleFile {
+    /// Lines of the form `mon-fri 07:00-18:00 Home`.
+    pub schedule: Vec<String>,
+    pub climates: Vec<ScheduleClimate>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScheduleClimate {
+    pub name: String,
+    pub climateRef: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub coolTemp: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub heatTemp: Option<i32>,
+}
+
+/// One difference between a live program and a [`ScheduleFile`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScheduleChange {
+    /// The blocks from `start` up to `end` on `day` move from one climate to another.  An `end`
+    /// of midnight is the end of the day.
+    Block {
+        day: Weekday,
+        start: NaiveTime,
+        end: NaiveTime,
+        from: String,
+        to: String,
+    },
+    /// A climate's `coolTemp` or `heatTemp` changes.
+    Setpoint {
+        climate: String,
+        setpoint: &'static str,
+        from: Option<i32>,
+        to: i32,
+    },
+    /// The file has a climate the program does not.
+    ClimateAdded { name: String },
+    /// The file gives a climate of the program, matched by `climateRef`, a new name.
+    ClimateRenamed { from: String, to: String },
+}
+
+impl fmt::Display for ScheduleChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScheduleChange::Block {
+                day,
+                start,
+                end,
+                from,
+                to,
+            } => write!(
+                f,
+                "{} {}-{}: {from} -> {to}",
+                DAY_NAMES[day.num_days_from_monday() as usize],
+                start.format("%H:%M"),
+                end.format("%H:%M")
+            ),
+            ScheduleChange::Setpoint {
+                climate,
+                setpoint,
+                from: Some(from),
+                to,
+            } => write!(f, "{climate} {setpoint}: {from} -> {to}"),
+            ScheduleChange::Setpoint {
+                climate,
+                setpoint,
+                from: None,
+                to,
+            } => write!(f, "{climate} {setpoint}: unset -> {to}"),
+            ScheduleChange::ClimateAdded { name } => write!(f, "new climate {name}"),
+            ScheduleChange::ClimateRenamed { from, to } => {
+                write!(f, "climate {from} renamed to {to}")
+            }
+        }
+    }
+}
+
+impl ScheduleFile {
+    /// Describe a program fetched with `includeProgram`.
+    pub fn from_program(program: &Program) -> Result<Self, EcobeeError> {
+        program.validate()?;
+        let climates = program
+            .climates
+            .iter()
+            .flatten()
+            .map(|climate| match (&climate.name, &climate.climateRef) {
+                (Some(name), Some(climate_ref)) => Ok(ScheduleClimate {
+                    name: name.clone(),
+                    climateRef: climate_ref.clone(),
+                    coolTemp: climate.coolTemp,
+                    heatTemp: climate.heatTemp,
+                }),
+                _ => Err(EcobeeError::Validation(
+                    "every climate needs a name and a climateRef".to_string(),
+                )),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let name = |climate_ref: &str| {
+            // validate() made sure every block names a climate.
+            program
+                .climate(climate_ref)
+                .and_then(|c| c.name.clone())
+                .unwrap_or_default()
+        };
+        let days: Vec<Vec<(usize, usize, String)>> = program
+            .schedule
+            .iter()
+            .flatten()
+            .map(|day| {
+                runs(day.len(), |b| &day[b])
+                    .into_iter()
+                    .map(|(start, end)| (start, end, name(&day[start])))
+                    .collect()
+            })
+            .collect();
+        let mut schedule = Vec::new();
+        let mut first = 0;
+        while first < days.len() {
+            let mut last = first;
+            while last + 1 < days.len() && days[last + 1] == days[first] {
+                last += 1;
+            }
+            let label = if first == last {
+                DAY_NAMES[first].to_string()
+            } else {
+                format!("{}-{}", DAY_NAMES[first], DAY_NAMES[last])
+            };
+            for (start, end, name) in &days[first] {
+                schedule.push(format!("{label} {}-{} {name}", clock(*start), clock(*end)));
+            }
+            first = last + 1;
+        }
+        Ok(ScheduleFile { schedule, climates })
+    }
+
+    pub fn from_toml(toml: &str) -> Result<Self, EcobeeError> {
+        let file: ScheduleFile =
+            toml::from_str(toml).map_err(|e| EcobeeError::Validation(e.to_string()))?;
+        file.grid()?;
+        Ok(file)
+    }
+
+    pub fn to_toml(&self) -> Result<String, EcobeeError> {
+        toml::to_string_pretty(self).map_err(|e| EcobeeError::Validation(e.to_string()))
+    }
+
+    /// Everything that would change if this file were applied to `live`, in schedule order.
+    pub fn diff(&self, live: &Program) -> Result<Vec<ScheduleChange>, EcobeeError> {
+        live.validate()?;
+        let wanted = self.grid()?;
+        let mut changes = Vec::new();
+        for climate in &self.climates {
+            // A live climate the file does not list keeps its name, so nothing else can take it.
+            let taken_by = live.climates.iter().flatten().find(|other| {
+                other.name.as_ref() == Some(&climate.name)
+                    && other.climateRef.as_deref().is_none_or(|other_ref| {
+                        !self.climates.iter().any(|c| c.climateRef == other_ref)
+                    })
+            });
+            if let Some(other) = taken_by {
+                return Err(EcobeeError::Validation(format!(
+                    "climate {:?} cannot be named {:?}, climate {:?} already is",
+                    climate.climateRef,
+                    climate.name,
+                    other.climateRef.as_deref().unwrap_or_default()
+                )));
+            }
+            let existing = match live.climate(&climate.climateRef) {
+                Some(existing) => existing,
+                None => {
+                    changes.push(ScheduleChange::ClimateAdded {
+                        name: climate.name.clone(),
+                    });
+                    continue;
+                }
+            };
+            if existing.name.as_ref() != Some(&climate.name) {
+                changes.push(ScheduleChange::ClimateRenamed {
+                    from: existing.name.clone().unwrap_or_default(),
+                    to: climate.name.clone(),
+                });
+            }
+            let setpoints = [
+                ("coolTemp", existing.coolTemp, climate.coolTemp),
+                ("heatTemp", existing.heatTemp, climate.heatTemp),
+            ];
+            for (setpoint, from, to) in setpoints {
+                match to {
+                    Some(to) if from != Some(to) => changes.push(ScheduleChange::Setpoint {
+                        climate: climate.name.clone(),
+                        setpoint,
+                        from,
+                        to,
+                    }),
+                    _ => {}
+                }
+            }
+        }
+        let live_name = |climate_ref: &str| {
+            live.climate(climate_ref)
+                .and_then(|c| c.name.clone())
+                .unwrap_or_default()
+        };
+        let schedule = live.schedule()?;
+        for (day, (live_day, wanted_day)) in schedule.iter().zip(&wanted).enumerate() {
+            let pair = |b: usize| (&live_day[b], &wanted_day[b]);
+            for (start, end) in runs(Program::BLOCKS_PER_DAY, pair) {
+                let (from, to) = pair(start);
+                if from != to {
+                    changes.push(ScheduleChange::Block {
+                        day: WEEKDAYS[day],
+                        start: time(start),
+                        end: time(end % Program::BLOCKS_PER_DAY),
+                        from: live_name(from),
+                        to: self.name(to).to_string(),
+                    });
+                }
+            }
+        }
+        Ok(changes)
+    }
+
+    /// Bring `program` in line with this file, touching only what [`ScheduleFile::diff`] reports.
+    /// `program` is left as it was when this fails.
+    pub fn apply_to(&self, program: &mut Program) -> Result<Vec<ScheduleChange>, EcobeeError> {
+        let changes = self.diff(program)?;
+        let mut edited = program.clone();
+        let mut added = Vec::new();
+        for climate in &self.climates {
+            match edited.climate_mut(&climate.climateRef) {
+                Some(existing) => {
+                    existing.name = Some(climate.name.clone());
+                    existing.coolTemp = climate.coolTemp.or(existing.coolTemp);
+                    existing.heatTemp = climate.heatTemp.or(existing.heatTemp);
+                }
+                None => added.push(climate),
+            }
+        }
+        // Only once every rename is done are the names new climates take free.
+        for climate in added {
+            edited.add_climate(Climate {
+                name: Some(climate.name.clone()),
+                climateRef: Some(climate.climateRef.clone()),
+                coolTemp: climate.coolTemp,
+                heatTemp: climate.heatTemp,
+                ..Climate::default()
+            })?;
+        }
+        edited.schedule = Some(self.grid()?);
+        edited.validate()?;
+        *program = edited;
+        Ok(changes)
+    }
+
+    fn name<'a>(&'a self, climate_ref: &'a str) -> &'a str {
+        self.climates
+            .iter()
+            .find(|climate| climate.climateRef == climate_ref)
+            .map_or(climate_ref, |climate| &climate.name)
+    }
+
+    /// The schedule as `climateRef`s, seven days of half hour blocks.
+    fn grid(&self) -> Result<Vec<Vec<String>>, EcobeeError> {
+        for (i, climate) in self.climates.iter().enumerate() {
+            let duplicate = self.climates[..i].iter().any(|earlier| {
+                earlier.name == climate.name || earlier.climateRef == climate.climateRef
+            });
+            if duplicate {
+                return Err(EcobeeError::Validation(format!(
+                    "more than one climate is named {:?} or has ref {:?}",
+                    climate.name, climate.climateRef
+                )));
+            }
+        }
+        let mut grid = vec![vec![None; Program::BLOCKS_PER_DAY]; Program::DAYS];
+        for line in &self.schedule {
+            let invalid = |reason: String| {
+                EcobeeError::Validation(format!("schedule line {line:?} {reason}"))
+            };
+            let (days, times, name) = fields(line)
+                .ok_or_else(|| invalid("is not \"days HH:MM-HH:MM climate\"".to_string()))?;
+            let (first_day, last_day) =
+                range(days, |day| DAY_NAMES.iter().position(|name| *name == day))
+                    .ok_or_else(|| invalid(format!("has unknown days {days:?}")))?;
+            let (start, end) = range(times, parse_clock)
+                .ok_or_else(|| invalid(format!("has malformed times {times:?}")))?;
+            if first_day > last_day || start >= end {
+                return Err(invalid("has a range that runs backwards".to_string()));
+            }
+            let climate = self
+                .climates
+                .iter()
+                .find(|climate| climate.name == name)
+                .ok_or_else(|| invalid(format!("uses climate {name:?}, which is not listed")))?;
+            for day in &mut grid[first_day..=last_day] {
+                for slot in &mut day[start..end] {
+                    if slot.is_some() {
+                        return Err(invalid("overlaps an earlier line".to_string()));
+                    }
+                    *slot = Some(climate.climateRef.clone());
+                }
+            }
+        }
+        grid.into_iter()
+            .enumerate()
+            .map(|(day, blocks)| {
+                blocks
+                    .into_iter()
+                    .enumerate()
+                    .map(|(b, slot)| {
+                        slot.ok_or_else(|| {
+                            EcobeeError::Validation(format!(
+                                "the schedule leaves {} {} empty",
+                                DAY_NAMES[day],
+                                clock(b)
+                            ))
+                        })
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+/// The days, times and climate name of a schedule line.  Any run of whitespace separates them,
+/// but the name is kept as written.
+fn fields(line: &str) -> Option<(&str, &str, &str)> {
+    let (days, rest) = line.trim().split_once(char::is_whitespace)?;
+    let (times, name) = rest.trim_start().split_once(char::is_whitespace)?;
+    Some((days, times, name.trim_start()))
+}
+
+/// Split `0..len` into the longest runs over which `key` stays the same, as `(start, end)`.
+fn runs<K: PartialEq>(len: usize, key: impl Fn(usize) -> K) -> Vec<(usize, usize)> {
+    let mut runs: Vec<(usize, usize)> = Vec::new();
+    for b in 0..len {
+        match runs.last_mut() {
+            Some((start, end)) if key(*start) == key(b) => *end = b + 1,
+            _ => runs.push((b, b + 1)),
+        }
+    }
+    runs
+}
+
+/// `first-last` or a lone `first`, both read by `parse`.
+fn range<T: Copy>(text: &str, parse: impl Fn(&str) -> Option<T>) -> Option<(T, T)> {
+    match text.split_once('-') {
+        Some((first, last)) => Some((parse(first)?, parse(last)?)),
+        None => parse(text).map(|only| (only, only)),
+    }
+}
+
+/// The block starting at `HH:MM`, where `24:00` is the end of the day.
+fn parse_clock(text: &str) -> Option<usize> {
+    if text == "24:00" {
+        return Some(Program::BLOCKS_PER_DAY);
+    }
+    NaiveTime::parse_from_str(text, "%H:%M")
+        .ok()
+        .and_then(|time| block(time).ok())
+}
+
+fn clock(b: usize) -> String {
+    format!("{:02}:{:02}", b / 2, b % 2 * 30)
+}
+
+fn time(b: usize) -> NaiveTime {
+    NaiveTime::from_hms_opt((b / 2) as u32, (b % 2 * 30) as u32, 0).expect("a block is in a day")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FILE: &str = r#"
+schedule = [
+    "mon-fri 00:00-07:00 Sleep",
+    "mon-fri 07:00-18:00 Home",
+    "mon-fri 18:00-24:00 Sleep",
+    "sat-sun 00:00-24:00 Home",
+]
+
+[[climates]]
+name = "Home"
+climateRef = "home"
+coolTemp = 760
+heatTemp = 700
+
+[[climates]]
+name = "Sleep"
+climateRef = "sleep"
+"#;
+
+    fn climate(name: &str, climate_ref: &str, cool: i32, heat: i32) -> Climate {
+        Climate {
+            name: Some(name.to_string()),
+            climateRef: Some(climate_ref.to_string()),
+            coolTemp: Some(cool),
+            heatTemp: Some(heat),
+            ..Climate::default()
+        }
+    }
+
+    fn live() -> Program {
+        let mut program = Program {
+            schedule: Some(vec![
+                vec!["home".to_string(); Program::BLOCKS_PER_DAY];
+                Program::DAYS
+            ]),
+            climates: Some(vec![
+                climate("Home", "home", 760, 700),
+                climate("Sleep", "sleep", 780, 640),
+            ]),
+            currentClimateRef: Some("home".to_string()),
+        };
+        for day in &WEEKDAYS[..5] {
+            let midnight = NaiveTime::from_hms_opt(0, 0, 0).unwrap();
+            let seven = NaiveTime::from_hms_opt(7, 0, 0).unwrap();
+            let six = NaiveTime::from_hms_opt(18, 0, 0).unwrap();
+            program.set_block(*day, midnight, seven, "sleep").unwrap();
+            program.set_block(*day, six, midnight, "sleep").unwrap();
+        }
+        program
+    }
+
+    #[test]
+    fn round_trip() {
+        let file = ScheduleFile::from_toml(FILE).unwrap();
+        let exported = ScheduleFile::from_program(&live()).unwrap();
+        assert_eq!(exported.schedule, file.schedule);
+        assert_eq!(
+            ScheduleFile::from_toml(&exported.to_toml().unwrap()).unwrap(),
+            exported
+        );
+        assert_eq!(file.diff(&live()).unwrap(), vec![]);
+    }
+
+    #[test]
+    fn diff_and_apply() {
+        let file = FILE
+            .replace("mon-fri 07:00-18:00 Home", "mon-fri 07:00-08:30 Home")
+            .replace("mon-fri 18:00-24:00", "mon-fri 08:30-24:00")
+            .replace("coolTemp = 760", "coolTemp = 750");
+        let file = ScheduleFile::from_toml(&file).unwrap();
+        let mut program = live();
+        let changes = file.apply_to(&mut program).unwrap();
+        assert_eq!(changes.len(), 6);
+        assert_eq!(changes[0].to_string(), "Home coolTemp: 760 -> 750");
+        assert_eq!(changes[1].to_string(), "mon 08:30-18:00: Home -> Sleep");
+        assert_eq!(changes[5].to_string(), "fri 08:30-18:00: Home -> Sleep");
+        assert_eq!(program.climate("home").unwrap().coolTemp, Some(750));
+        assert_eq!(program.climate("sleep").unwrap().heatTemp, Some(640));
+        assert_eq!(file.diff(&program).unwrap(), vec![]);
+    }
+
+    #[test]
+    fn renamed_climates() {
+        let file = ScheduleFile::from_toml(&FILE.replace("Sleep", "Night")).unwrap();
+        let mut program = live();
+        let changes = file.apply_to(&mut program).unwrap();
+        assert_eq!(
+            changes,
+            vec![ScheduleChange::ClimateRenamed {
+                from: "Sleep".to_string(),
+                to: "Night".to_string(),
+            }]
+        );
+        assert_eq!(changes[0].to_string(), "climate Sleep renamed to Night");
+        assert_eq!(
+            program.climate("sleep").unwrap().name.as_deref(),
+            Some("Night")
+        );
+        assert_eq!(file.diff(&program).unwrap(), vec![]);
+
+        // Taking the name of a climate the file leaves alone would leave two with that name.
+        let mut program = live();
+        program
+            .add_climate(climate("Away", "away", 820, 600))
+            .unwrap();
+        let file = ScheduleFile::from_toml(
+            &FILE
+                .replace("\"Sleep\"", "\"Away\"")
+                .replace(" Sleep\"", " Away\""),
+        )
+        .unwrap();
+        match file.diff(&program) {
+            Err(EcobeeError::Validation(message)) => assert!(message.contains("already is")),
+            other => panic!("expected a clash, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn added_climates() {
+        let mut program = live();
+        program
+            .add_climate(climate("Away", "away", 820, 600))
+            .unwrap();
+        let vacation = "\n[[climates]]\nname = \"Away\"\nclimateRef = \"vacation\"\n";
+
+        // The live Away keeps its name, so the new climate cannot have it too.
+        let file = format!("{}{vacation}", FILE.replace("Sleep", "Night"));
+        let file = ScheduleFile::from_toml(&file).unwrap();
+        assert!(file.diff(&program).is_err());
+        let before = program.clone();
+        assert!(file.apply_to(&mut program).is_err());
+        assert_eq!(program, before);
+
+        // Unless the file renames it first, even when the rename comes later in the file.
+        let file =
+            format!("{FILE}{vacation}\n[[climates]]\nname = \"Trip\"\nclimateRef = \"away\"\n");
+        let file = ScheduleFile::from_toml(&file).unwrap();
+        let changes = file.apply_to(&mut program).unwrap();
+        assert_eq!(
+            changes.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            vec!["new climate Away", "climate Away renamed to Trip"]
+        );
+        assert_eq!(
+            program.climate("away").unwrap().name.as_deref(),
+            Some("Trip")
+        );
+        assert_eq!(
+            program.climate("vacation").unwrap().name.as_deref(),
+            Some("Away")
+        );
+        assert_eq!(file.diff(&program).unwrap(), vec![]);
+    }
+
+    #[test]
+    fn malformed_files() {
+        let parse = |from: &str, to: &str| ScheduleFile::from_toml(&FILE.replace(from, to));
+        assert!(parse("sat-sun 00:00-24:00", "sat 00:00-24:00").is_err());
+        assert!(parse("sat-sun 00:00-24:00", "sat-sun 00:00-24:15").is_err());
+        assert!(parse("sat-sun", "sun-sat").is_err());
+        assert!(parse("sat-sun 00:00-24:00 Home", "sat-sun 00:00-24:00 Away").is_err());
+        assert!(parse("mon-fri 07:00-18:00", "mon-fri 06:00-18:00").is_err());
+        assert!(parse("mon-fri 07:00-18:00 Home", "mon-fri").is_err());
+
+        let duplicate = |from: &str, to: &str| match parse(from, to) {
+            Err(EcobeeError::Validation(message)) => message.starts_with("more than one climate"),
+            _ => false,
+        };
+        assert!(duplicate("name = \"Sleep\"", "name = \"Home\""));
+        assert!(duplicate("climateRef = \"sleep\"", "climateRef = \"home\""));
+    }
+
+    #[test]
+    fn extra_whitespace() {
+        let file = FILE
+            .replace("mon-fri 07:00-18:00 Home", "mon-fri  07:00-18:00\tHome")
+            .replace(
+                "sat-sun 00:00-24:00 Home",
+                "  sat-sun   00:00-24:00   Home ",
+            );
+        assert_eq!(
+            ScheduleFile::from_toml(&file)
+                .unwrap()
+                .diff(&live())
+                .unwrap(),
+            vec![]
+        );
+    }
+}
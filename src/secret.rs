@@ -0,0 +1,127 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// A credential that must never end up in logs.
+///
+/// `Debug` and `Display` print a placeholder, so a `Secret` can sit in any struct that gets
+/// logged.  The value is only reachable through [`Secret::expose`], or by serializing it, which is
+/// how token stores write it out.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Secret(String);
+
+impl Secret {
+    pub fn new(value: impl Into<String>) -> Self {
+        Secret(value.into())
+    }
+
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<String> for Secret {
+    fn from(value: String) -> Self {
+        Secret(value)
+    }
+}
+
+impl From<&str> for Secret {
+    fn from(value: &str) -> Self {
+        Secret(value.to_string())
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Secret([REDACTED])")
+    }
+}
+
+impl fmt::Display for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("[REDACTED]")
+    }
+}
+
+/// Keys whose values are credentials wherever they show up in an API body.
+#[cfg_attr(not(feature = "trace-bodies"), allow(dead_code))]
+pub(crate) const SECRET_KEYS: &[&str] = &["access_token", "refresh_token", "client_id", "api_key"];
+
+/// Token endpoint bodies also carry the authorization `code`.  Elsewhere `code` is the number in
+/// a `Status`, which is worth seeing.
+#[cfg_attr(not(feature = "trace-bodies"), allow(dead_code))]
+pub(crate) const TOKEN_SECRET_KEYS: &[&str] = &[
+    "access_token",
+    "refresh_token",
+    "code",
+    "client_id",
+    "api_key",
+];
+
+/// A copy of a JSON body that is safe to log.  Bodies that are not JSON are left out entirely
+/// since there is no telling what they contain.
+#[cfg_attr(not(feature = "trace-bodies"), allow(dead_code))]
+pub(crate) fn redact_json(body: &str, keys: &[&str]) -> String {
+    fn redact(value: &mut serde_json::Value, keys: &[&str]) {
+        match value {
+            serde_json::Value::Object(map) => {
+                for (key, value) in map.iter_mut() {
+                    if keys.contains(&key.as_str()) {
+                        *value = serde_json::Value::String("[REDACTED]".to_string());
+                    } else {
+                        redact(value, keys);
+                    }
+                }
+            }
+            serde_json::Value::Array(values) => {
+                values.iter_mut().for_each(|value| redact(value, keys))
+            }
+            _ => {}
+        }
+    }
+    match serde_json::from_str::<serde_json::Value>(body) {
+        Ok(mut value) => {
+            redact(&mut value, keys);
+            value.to_string()
+        }
+        Err(_) => format!("<{} byte non-JSON body>", body.len()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redacted() {
+        let secret = Secret::from("hunter2");
+        assert_eq!(format!("{secret}"), "[REDACTED]");
+        assert_eq!(format!("{secret:?}"), "Secret([REDACTED])");
+        assert_eq!(secret.expose(), "hunter2");
+        assert_eq!(serde_json::to_string(&secret).unwrap(), r#""hunter2""#);
+    }
+
+    #[test]
+    fn redacted_json_body() {
+        assert_eq!(
+            redact_json(
+                r#"{"access_token":"a","nested":[{"refresh_token":"r","scope":"smartWrite"}]}"#,
+                SECRET_KEYS
+            ),
+            r#"{"access_token":"[REDACTED]","nested":[{"refresh_token":"[REDACTED]","scope":"smartWrite"}]}"#
+        );
+        assert_eq!(
+            redact_json("grant_type=refresh_token&refresh_token=r", SECRET_KEYS),
+            "<40 byte non-JSON body>"
+        );
+        assert_eq!(
+            redact_json(r#"{"status":{"code":0,"message":""}}"#, SECRET_KEYS),
+            r#"{"status":{"code":0,"message":""}}"#
+        );
+        assert_eq!(
+            redact_json(r#"{"ecobeePin":"bv29","code":"dv0Mk"}"#, TOKEN_SECRET_KEYS),
+            r#"{"code":"[REDACTED]","ecobeePin":"bv29"}"#
+        );
+    }
+}
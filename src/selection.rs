@@ -0,0 +1,326 @@
+use crate::EcobeeError;
+use serde::{Deserialize, Serialize, Serializer};
+use std::collections::BTreeSet;
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+
+/// ecobee refuses requests that select more thermostats than this by identifier.
+pub const MAX_THERMOSTATS_PER_SELECTION: usize = 25;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum SelectionType {
+    registered,
+    thermostats,
+    managementSet,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+pub enum SelectionInclude {
+    includeRuntime,
+    includeExtendedRuntime,
+    includeElectricity,
+    includeSettings,
+    includeLocation,
+    includeProgram,
+    includeEvents,
+    includeDevice,
+    includeTechnician,
+    includeUtility,
+    includeManagement,
+    includeAlerts,
+    includeReminders,
+    includeWeather,
+    includeHouseDetails,
+    includeOemCfg,
+    includeEquipmentStatus,
+    includeNotificationSettings,
+    includePrivacy,
+    includeVersion,
+    includeSecuritySettings,
+    includeSensors,
+    includeAudio,
+    includeEnergy,
+    includeCapabilities,
+}
+
+/// A thermostat identifier, the serial number shown under "About" on the thermostat.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct ThermostatId(String);
+
+impl ThermostatId {
+    pub fn new(identifier: impl Into<String>) -> Result<Self, EcobeeError> {
+        let identifier = identifier.into();
+        if identifier.is_empty() {
+            return Err(EcobeeError::Validation(
+                "thermostat identifiers cannot be empty".to_string(),
+            ));
+        }
+        if identifier.contains(|c: char| c == ',' || c.is_whitespace()) {
+            return Err(EcobeeError::Validation(format!(
+                "thermostat identifier {identifier:?} cannot contain commas or whitespace"
+            )));
+        }
+        Ok(ThermostatId(identifier))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl FromStr for ThermostatId {
+    type Err = EcobeeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ThermostatId::new(s)
+    }
+}
+
+impl TryFrom<String> for ThermostatId {
+    type Error = EcobeeError;
+
+    fn try_from(identifier: String) -> Result<Self, Self::Error> {
+        ThermostatId::new(identifier)
+    }
+}
+
+impl From<ThermostatId> for String {
+    fn from(identifier: ThermostatId) -> Self {
+        identifier.0
+    }
+}
+
+impl AsRef<str> for ThermostatId {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for ThermostatId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// A node in the management set tree of an EMS account, such as `/Toronto/Campus/BuildingA`.
+/// `/` is the root of the tree.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ManagementSetPath(String);
+
+impl ManagementSetPath {
+    pub fn new(path: impl Into<String>) -> Result<Self, EcobeeError> {
+        let path = path.into();
+        let invalid = |reason: &str| {
+            Err(EcobeeError::Validation(format!(
+                "management set path {path:?} {reason}"
+            )))
+        };
+        if !path.starts_with('/') {
+            return invalid("must start with '/'");
+        }
+        if path != "/" && path[1..].split('/').any(str::is_empty) {
+            return invalid("cannot contain empty segments or end with '/'");
+        }
+        Ok(ManagementSetPath(path))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl FromStr for ManagementSetPath {
+    type Err = EcobeeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ManagementSetPath::new(s)
+    }
+}
+
+impl fmt::Display for ManagementSetPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum SelectionMatch {
+    Registered,
+    Thermostats(Vec<ThermostatId>),
+    ManagementSet(ManagementSetPath),
+}
+
+/// Which thermostats a request applies to, and which parts of them to return.
+///
+/// ```
+/// use ecobee::{Selection, SelectionInclude, ThermostatId};
+///
+/// let thermostat: ThermostatId = "522697894617".parse()?;
+/// let selection = Selection::thermostats(&[thermostat])?
+///     .with(SelectionInclude::includeRuntime)
+///     .with(SelectionInclude::includeSensors);
+/// # Ok::<(), ecobee::EcobeeError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Selection {
+    selectionMatch: SelectionMatch,
+    include: BTreeSet<SelectionInclude>,
+}
+
+impl Selection {
+    /// Every thermostat registered to the account.
+    pub fn registered() -> Self {
+        Self::new(SelectionMatch::Registered)
+    }
+
+    /// Between one and [`MAX_THERMOSTATS_PER_SELECTION`] thermostats by identifier.
+    pub fn thermostats(identifiers: &[ThermostatId]) -> Result<Self, EcobeeError> {
+        if identifiers.is_empty() {
+            return Err(EcobeeError::Validation(
+                "a thermostats selection needs at least one identifier".to_string(),
+            ));
+        }
+        if identifiers.len() > MAX_THERMOSTATS_PER_SELECTION {
+            return Err(EcobeeError::Validation(format!(
+                "a selection can hold at most {MAX_THERMOSTATS_PER_SELECTION} thermostats, got {}",
+                identifiers.len()
+            )));
+        }
+        Ok(Self::new(SelectionMatch::Thermostats(identifiers.to_vec())))
+    }
+
+    /// The thermostats under a node of the management set tree.
+    pub fn management_set(path: ManagementSetPath) -> Self {
+        Self::new(SelectionMatch::ManagementSet(path))
+    }
+
+    /// A thermostats selection with nothing selected yet, only good as a placeholder.
+    pub(crate) fn empty() -> Self {
+        Self::new(SelectionMatch::Thermostats(Vec::new()))
+    }
+
+    fn new(selection_match: SelectionMatch) -> Self {
+        Selection {
+            selectionMatch: selection_match,
+            include: BTreeSet::new(),
+        }
+    }
+
+    /// Also return the part of the thermostat that `include` asks for.
+    pub fn with(mut self, include: SelectionInclude) -> Self {
+        self.include.insert(include);
+        self
+    }
+
+    pub fn selection_type(&self) -> SelectionType {
+        match self.selectionMatch {
+            SelectionMatch::Registered => SelectionType::registered,
+            SelectionMatch::Thermostats(_) => SelectionType::thermostats,
+            SelectionMatch::ManagementSet(_) => SelectionType::managementSet,
+        }
+    }
+
+    /// The `selectionMatch` string sent to ecobee.
+    pub fn selection_match(&self) -> String {
+        match &self.selectionMatch {
+            SelectionMatch::Registered => String::new(),
+            SelectionMatch::Thermostats(identifiers) => identifiers
+                .iter()
+                .map(ThermostatId::as_str)
+                .collect::<Vec<_>>()
+                .join(","),
+            SelectionMatch::ManagementSet(path) => path.to_string(),
+        }
+    }
+
+    /// The thermostats picked by identifier, empty for the other selection types.
+    pub fn thermostat_ids(&self) -> &[ThermostatId] {
+        match &self.selectionMatch {
+            SelectionMatch::Thermostats(identifiers) => identifiers,
+            _ => &[],
+        }
+    }
+
+    pub fn includes(&self) -> impl Iterator<Item = SelectionInclude> + '_ {
+        self.include.iter().copied()
+    }
+}
+
+impl Serialize for Selection {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        use serde::ser::SerializeMap;
+
+        let mut map = serializer.serialize_map(None)?;
+        map.serialize_entry("selectionType", &self.selection_type())?;
+        map.serialize_entry("selectionMatch", &self.selection_match())?;
+        for include in &self.include {
+            map.serialize_entry(include, &true)?;
+        }
+        map.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids(count: usize) -> Vec<ThermostatId> {
+        (0..count)
+            .map(|i| ThermostatId::new(format!("5226978946{i:02}")).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn thermostat_ids() {
+        assert!(ThermostatId::new("522697894617").is_ok());
+        assert!(ThermostatId::new("").is_err());
+        assert!(ThermostatId::new("522697894617,522697894618").is_err());
+        assert!(serde_json::from_str::<ThermostatId>(r#""""#).is_err());
+    }
+
+    #[test]
+    fn thermostats_selection() {
+        let selection = Selection::thermostats(&ids(2))
+            .unwrap()
+            .with(SelectionInclude::includeSensors);
+        assert_eq!(selection.selection_type(), SelectionType::thermostats);
+        assert_eq!(selection.selection_match(), "522697894600,522697894601");
+        assert_eq!(
+            selection.includes().collect::<Vec<_>>(),
+            vec![SelectionInclude::includeSensors]
+        );
+        assert!(Selection::thermostats(&[]).is_err());
+        assert!(Selection::thermostats(&ids(MAX_THERMOSTATS_PER_SELECTION)).is_ok());
+        assert!(Selection::thermostats(&ids(MAX_THERMOSTATS_PER_SELECTION + 1)).is_err());
+    }
+
+    #[test]
+    fn management_set_paths() {
+        assert!(ManagementSetPath::new("/").is_ok());
+        assert!(ManagementSetPath::new("/Toronto/Campus/BuildingA").is_ok());
+        assert!(ManagementSetPath::new("Toronto").is_err());
+        assert!(ManagementSetPath::new("/Toronto/").is_err());
+        assert!(ManagementSetPath::new("/Toronto//Campus").is_err());
+    }
+
+    #[test]
+    fn selection_json() {
+        assert_eq!(
+            serde_json::to_string(&Selection::registered()).unwrap(),
+            r#"{"selectionType":"registered","selectionMatch":""}"#
+        );
+        let path = ManagementSetPath::new(r#"/Toronto/"Main" St"#).unwrap();
+        let json = serde_json::to_string(&Selection::management_set(path)).unwrap();
+        assert_eq!(
+            json,
+            r#"{"selectionType":"managementSet","selectionMatch":"/Toronto/\"Main\" St"}"#
+        );
+        let round_trip: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(round_trip["selectionMatch"], r#"/Toronto/"Main" St"#);
+    }
+}
@@ -0,0 +1,1311 @@
+//! The thermostat object returned by `GET /thermostat` and accepted by `POST /thermostat`.
+//!
+//! Field names and units follow the ecobee object reference
+//! (<https://www.ecobee.com/home/developer/api/documentation/v1/objects/Thermostat.shtml>).
+//! Every sub-object is only present when the matching [`SelectionInclude`](crate::SelectionInclude)
+//! was set, and ecobee leaves out fields freely, so nearly everything is an `Option`.  Unset fields
+//! are left out when serializing, which is what makes partial updates possible.
+
+use crate::{FanMode, ThermostatId};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Thermostat {
+    /// TODO: the spec says this is required but I suspect it might not be.
+    pub identifier: ThermostatId,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thermostatRev: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub isRegistered: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub modelNumber: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub brand: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub features: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lastModified: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thermostatTime: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub utcTime: Option<String>,
+    /// Comma separated list of the equipment currently running, empty when idle.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub equipmentStatus: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alerts: Option<Vec<Alert>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reminders: Option<Vec<ThermostatReminder>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub settings: Option<Settings>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub runtime: Option<Runtime>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extendedRuntime: Option<ExtendedRuntime>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub electricity: Option<Electricity>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub devices: Option<Vec<Device>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<Location>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub energy: Option<Energy>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub technician: Option<Technician>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub utility: Option<Utility>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub management: Option<Management>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub weather: Option<Weather>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub events: Option<Vec<Event>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub program: Option<Program>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub houseDetails: Option<HouseDetails>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub oemCfg: Option<OemCfg>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notificationSettings: Option<NotificationSettings>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub privacy: Option<Privacy>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<Version>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub securitySettings: Option<SecuritySettings>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remoteSensors: Option<Vec<RemoteSensor>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub audio: Option<Audio>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub capabilities: Option<Capabilities>,
+}
+
+impl Thermostat {
+    /// A thermostat with nothing but its identifier set, the starting point for an update:
+    /// `Thermostat { settings: Some(settings), ..Thermostat::new(identifier) }`.
+    pub fn new(identifier: ThermostatId) -> Self {
+        Thermostat {
+            identifier,
+            name: None,
+            thermostatRev: None,
+            isRegistered: None,
+            modelNumber: None,
+            brand: None,
+            features: None,
+            lastModified: None,
+            thermostatTime: None,
+            utcTime: None,
+            equipmentStatus: None,
+            alerts: None,
+            reminders: None,
+            settings: None,
+            runtime: None,
+            extendedRuntime: None,
+            electricity: None,
+            devices: None,
+            location: None,
+            energy: None,
+            technician: None,
+            utility: None,
+            management: None,
+            weather: None,
+            events: None,
+            program: None,
+            houseDetails: None,
+            oemCfg: None,
+            notificationSettings: None,
+            privacy: None,
+            version: None,
+            securitySettings: None,
+            remoteSensors: None,
+            audio: None,
+            capabilities: None,
+        }
+    }
+}
+
+/// OEM specific configuration.  ecobee does not document its shape, so it is kept as raw JSON.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct OemCfg(pub serde_json::Map<String, serde_json::Value>);
+
+/// Privacy preferences.  ecobee does not document its shape, so it is kept as raw JSON.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Privacy(pub serde_json::Map<String, serde_json::Value>);
+
+/// Features the thermostat hardware supports.  ecobee does not document its shape, so it is kept
+/// as raw JSON.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Capabilities(pub serde_json::Map<String, serde_json::Value>);
+
+/// The thermostat's connection state and latest readings.  Temperatures are in tenths of a degree
+/// Fahrenheit.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Runtime {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub runtimeRev: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub connected: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub firstConnected: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub connectDateTime: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub disconnectDateTime: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lastModified: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lastStatusModified: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub runtimeDate: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub runtimeInterval: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub actualTemperature: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub actualHumidity: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rawTemperature: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub showIconMode: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub desiredHeat: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub desiredCool: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub desiredHumidity: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub desiredDehumidity: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub desiredFanMode: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub actualVOC: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub actualCO2: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub actualAQAccuracy: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub actualAQScore: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub desiredHeatRange: Option<Vec<i32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub desiredCoolRange: Option<Vec<i32>>,
+}
+
+/// The last three five-minute intervals of runtime data, oldest first.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ExtendedRuntime {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lastReadingTimestamp: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub runtimeDate: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub runtimeInterval: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub actualTemperature: Option<Vec<i32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub actualHumidity: Option<Vec<i32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub desiredHeat: Option<Vec<i32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub desiredCool: Option<Vec<i32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub desiredHumidity: Option<Vec<i32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub desiredDehumidity: Option<Vec<i32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dmOffset: Option<Vec<i32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hvacMode: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub heatPump1: Option<Vec<i32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub heatPump2: Option<Vec<i32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auxHeat1: Option<Vec<i32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auxHeat2: Option<Vec<i32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auxHeat3: Option<Vec<i32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cool1: Option<Vec<i32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cool2: Option<Vec<i32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fan: Option<Vec<i32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub humidifier: Option<Vec<i32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dehumidifier: Option<Vec<i32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub economizer: Option<Vec<i32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ventilator: Option<Vec<i32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub currentElectricityBill: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub projectedElectricityBill: Option<i32>,
+}
+
+/// Electricity usage reported by connected meters.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Electricity {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub devices: Option<Vec<ElectricityDevice>>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ElectricityDevice {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tiers: Option<Vec<ElectricityTier>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lastUpdate: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cost: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub consumption: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ElectricityTier {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub consumption: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cost: Option<String>,
+}
+
+/// The thermostat's configuration.  Temperatures are in tenths of a degree Fahrenheit.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Settings {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hvacMode: Option<HvacMode>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lastServiceDate: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub serviceRemindMe: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub monthsBetweenService: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remindMeDate: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vent: Option<Vent>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ventilatorMinOnTime: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub serviceRemindTechnician: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub eiLocation: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub coldTempAlert: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub coldTempAlertEnabled: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hotTempAlert: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hotTempAlertEnabled: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub coolStages: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub heatStages: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub maxSetBack: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub maxSetForward: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quickSaveSetBack: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quickSaveSetForward: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hasHeatPump: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hasForcedAir: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hasBoiler: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hasHumidifier: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hasErv: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hasHrv: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub condensationAvoid: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub useCelsius: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub useTimeFormat12: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub humidity: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub humidifierMode: Option<HumidifierMode>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub backlightOnIntensity: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub backlightSleepIntensity: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub backlightOffTime: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub soundTickVolume: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub soundAlertVolume: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compressorProtectionMinTime: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compressorProtectionMinTemp: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stage1HeatingDifferentialTemp: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stage1CoolingDifferentialTemp: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stage1HeatingDissipationTime: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stage1CoolingDissipationTime: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub heatPumpReversalOnCool: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fanControlRequired: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fanMinOnTime: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub heatCoolMinDelta: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tempCorrection: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub holdAction: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub heatPumpGroundWater: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hasElectric: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hasDehumidifier: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dehumidifierMode: Option<DehumidifierMode>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dehumidifierLevel: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dehumidifyWithAC: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dehumidifyOvercoolOffset: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub autoHeatCoolFeatureEnabled: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wifiOfflineAlert: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub heatMinTemp: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub heatMaxTemp: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub coolMinTemp: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub coolMaxTemp: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub heatRangeHigh: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub heatRangeLow: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub coolRangeHigh: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub coolRangeLow: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub userAccessCode: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub userAccessSetting: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auxRuntimeAlert: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auxOutdoorTempAlert: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auxMaxOutdoorTemp: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auxRuntimeAlertNotify: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auxOutdoorTempAlertNotify: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auxRuntimeAlertNotifyTechnician: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auxOutdoorTempAlertNotifyTechnician: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub disablePreHeating: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub disablePreCooling: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub installerCodeRequired: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub drAccept: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub isRentalProperty: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub useZoneController: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub randomStartDelayCool: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub randomStartDelayHeat: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub humidityHighAlert: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub humidityLowAlert: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub disableHeatPumpAlerts: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub disableAlertsOnIdt: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub humidityAlertNotify: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub humidityAlertNotifyTechnician: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tempAlertNotify: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tempAlertNotifyTechnician: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub monthlyElectricityBillLimit: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enableElectricityBillAlert: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enableProjectedElectricityBillAlert: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub electricityBillingDayOfMonth: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub electricityBillCycleMonths: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub electricityBillStartMonth: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ventilatorMinOnTimeHome: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ventilatorMinOnTimeAway: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub backlightOffDuringSleep: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub autoAway: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub smartCirculation: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub followMeComfort: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ventilatorType: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub isVentilatorTimerOn: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ventilatorOffDateTime: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hasUVFilter: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub coolingLockout: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ventilatorFreeCooling: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dehumidifyWhenHeating: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ventilatorDehumidify: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub groupRef: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub groupName: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub groupSetting: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fanSpeed: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HvacMode {
+    auto,
+    auxHeatOnly,
+    cool,
+    heat,
+    off,
+    /// A value this crate does not know about yet, kept as ecobee sent it so it can be written
+    /// back unchanged.
+    #[serde(untagged)]
+    unknown(String),
+}
+
+/// How the ventilator runs.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Vent {
+    auto,
+    minontime,
+    on,
+    off,
+    /// A value this crate does not know about yet, kept as ecobee sent it so it can be written
+    /// back unchanged.
+    #[serde(untagged)]
+    unknown(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HumidifierMode {
+    auto,
+    manual,
+    off,
+    /// A value this crate does not know about yet, kept as ecobee sent it so it can be written
+    /// back unchanged.
+    #[serde(untagged)]
+    unknown(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DehumidifierMode {
+    on,
+    off,
+    /// A value this crate does not know about yet, kept as ecobee sent it so it can be written
+    /// back unchanged.
+    #[serde(untagged)]
+    unknown(String),
+}
+
+/// Where the thermostat is installed.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Location {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeZoneOffsetMinutes: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeZone: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub isDaylightSaving: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub streetAddress: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub city: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provinceState: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub country: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub postalCode: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub phoneNumber: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mapCoordinates: Option<String>,
+}
+
+/// The weekly schedule: seven days of forty-eight half hour blocks, each naming a climate by its
+/// `climateRef`.  See the editing methods in [`Program::set_block`] and friends.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Program {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schedule: Option<Vec<Vec<String>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub climates: Option<Vec<Climate>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub currentClimateRef: Option<String>,
+}
+
+/// A named comfort setting such as Home, Away or Sleep.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Climate {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub climateRef: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub isOccupied: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub isOptimized: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub coolFan: Option<FanMode>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub heatFan: Option<FanMode>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vent: Option<Vent>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ventilatorMinOnTime: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub r#type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub colour: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub coolTemp: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub heatTemp: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sensors: Option<Vec<ClimateSensor>>,
+}
+
+/// A remote sensor input that takes part in a climate.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ClimateSensor {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+/// A hold, vacation, demand response or other event overriding the program.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Event {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub r#type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub running: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub startDate: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub startTime: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub endDate: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub endTime: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub isOccupied: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub isCoolOff: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub isHeatOff: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub coolHoldTemp: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub heatHoldTemp: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fan: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vent: Option<Vent>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ventilatorMinOnTime: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub isOptional: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub isTemperatureRelative: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub coolRelativeTemp: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub heatRelativeTemp: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub isTemperatureAbsolute: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dutyCyclePercentage: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fanMinOnTime: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub occupiedSensorActive: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unoccupiedSensorActive: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub drRampUpTemp: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub drRampUpTime: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub linkRef: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub holdClimateRef: Option<String>,
+}
+
+/// A piece of hardware attached to the thermostat, with its sensors and outputs.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Device {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deviceId: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sensors: Option<Vec<Sensor>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub outputs: Option<Vec<Output>>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Sensor {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub manufacturer: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub zone: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sensorId: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub r#type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub usage: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub numberOfBits: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bconstant: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thermistorSize: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tempCorrection: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gain: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub maxVoltage: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub multiplier: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub states: Option<Vec<State>>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct State {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub maxValue: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub minValue: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub r#type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub actions: Option<Vec<Action>>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Action {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub r#type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sendAlert: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sendUpdate: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub activationDelay: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deactivationDelay: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub minActionDuration: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub heatAdjustTemp: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub coolAdjustTemp: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub activateRelay: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub activateRelayOpen: Option<bool>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Output {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub zone: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub outputId: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub r#type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sendUpdate: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub activeClosed: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub activationTime: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deactivationTime: Option<i32>,
+}
+
+/// The contractor who installed the thermostat.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Technician {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contractorRef: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub phone: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub streetAddress: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub city: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provinceState: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub country: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub postalCode: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub web: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Utility {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub phone: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub web: Option<String>,
+}
+
+/// The company managing the thermostat.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Management {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub administrativeContact: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub billingContact: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub phone: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub web: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub showAlertIdt: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub showAlertWeb: Option<bool>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Alert {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub acknowledgeRef: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub date: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub severity: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alertNumber: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alertType: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub isOperatorAlert: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reminder: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub showIdt: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub showWeb: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sendEmail: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub acknowledgement: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remindMeLater: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thermostatIdentifier: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notificationType: Option<String>,
+}
+
+/// A maintenance reminder, such as a filter change.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ThermostatReminder {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thermostatIdentifier: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub r#type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remindMeDate: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remindTechnician: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Weather {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub weatherStation: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub forecasts: Option<Vec<WeatherForecast>>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WeatherForecast {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub weatherSymbol: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dateTime: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub condition: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pressure: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub relativeHumidity: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dewpoint: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub visibility: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub windSpeed: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub windGust: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub windDirection: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub windBearing: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pop: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tempHigh: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tempLow: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sky: Option<i32>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HouseDetails {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub style: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub numberOfFloors: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub numberOfRooms: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub numberOfOccupants: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub age: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub windowEfficiency: Option<i32>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NotificationSettings {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub emailAddresses: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub emailNotificationsEnabled: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub equipment: Option<Vec<EquipmentSetting>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub general: Option<Vec<GeneralSetting>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<Vec<LimitSetting>>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EquipmentSetting {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filterLastChanged: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filterLife: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filterLifeUnits: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remindMeDate: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub r#type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remindTechnician: Option<bool>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GeneralSetting {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub r#type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remindTechnician: Option<bool>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LimitSetting {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub r#type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remindTechnician: Option<bool>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Version {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thermostatFirmwareVersion: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SecuritySettings {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub userAccessCode: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allUserAccess: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub programAccess: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detailsAccess: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quickSaveAccess: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vacationAccess: Option<bool>,
+}
+
+/// The thermostat's own sensor or a wireless remote sensor.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RemoteSensor {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub r#type: Option<RemoteSensorType>,
+    /// The pairing code printed on remote sensors, absent for the thermostat's own sensor.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inUse: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub capability: Option<Vec<RemoteSensorCapability>>,
+}
+
+/// One reading of a remote sensor.  `value` is sent as a string whatever the `type`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RemoteSensorCapability {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub r#type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+}
+
+impl RemoteSensor {
+    /// Every reading this sensor reported that could be understood.
+    pub fn readings(&self) -> impl Iterator<Item = SensorReading> + '_ {
+        self.capability
+            .iter()
+            .flatten()
+            .filter_map(RemoteSensorCapability::reading)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RemoteSensorType {
+    thermostat,
+    ecobee3_remote_sensor,
+    monitor_sensor,
+    control_sensor,
+    /// A kind of sensor this crate does not know about yet, kept as ecobee sent it.
+    #[serde(untagged)]
+    unknown(String),
+}
+
+impl RemoteSensorCapability {
+    /// The typed value of this capability, or `None` when the sensor has nothing to report (ecobee
+    /// sends `"unknown"`) or the capability is not one of the ones below.
+    pub fn reading(&self) -> Option<SensorReading> {
+        let value = self.value.as_deref()?;
+        match self.r#type.as_deref()? {
+            "temperature" => value.parse().ok().map(SensorReading::temperature),
+            "humidity" => value.parse().ok().map(SensorReading::humidity),
+            "occupancy" => value.parse().ok().map(SensorReading::occupancy),
+            "dryContact" => match value {
+                "0" => Some(SensorReading::dryContact(false)),
+                "1" => Some(SensorReading::dryContact(true)),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+/// A parsed [`RemoteSensorCapability`] value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SensorReading {
+    /// Tenths of a degree Fahrenheit.
+    temperature(i32),
+    /// Percent relative humidity.
+    humidity(i32),
+    occupancy(bool),
+    /// `true` while the contact is closed.  ecobee reports `"0"` for an open contact.
+    dryContact(bool),
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Audio {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub playbackVolume: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub microphoneEnabled: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub soundAlertVolume: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub soundTickVolume: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub voiceEngines: Option<Vec<VoiceEngine>>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct VoiceEngine {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Energy {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub energyFeatureState: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub feelsLikeMode: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comfortPreferences: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn partial_thermostat_json() {
+        let thermostat = Thermostat {
+            settings: Some(Settings {
+                hvacMode: Some(HvacMode::heat),
+                fanMinOnTime: Some(15),
+                ..Settings::default()
+            }),
+            ..Thermostat::new("522697894617".parse().unwrap())
+        };
+        assert_eq!(
+            serde_json::to_string(&thermostat).unwrap(),
+            r#"{"identifier":"522697894617","settings":{"hvacMode":"heat","fanMinOnTime":15}}"#
+        );
+    }
+
+    #[test]
+    fn unknown_modes() {
+        let json = r#"{"hvacMode":"emergencyHeat","vent":"turbo","humidifierMode":"eco","dehumidifierMode":"eco"}"#;
+        let settings: Settings = serde_json::from_str(json).unwrap();
+        let unknown = |value: &str| value.to_string();
+        assert_eq!(
+            settings.hvacMode,
+            Some(HvacMode::unknown(unknown("emergencyHeat")))
+        );
+        assert_eq!(settings.vent, Some(Vent::unknown(unknown("turbo"))));
+        assert_eq!(
+            settings.humidifierMode,
+            Some(HumidifierMode::unknown(unknown("eco")))
+        );
+        assert_eq!(
+            settings.dehumidifierMode,
+            Some(DehumidifierMode::unknown(unknown("eco")))
+        );
+        assert_eq!(serde_json::to_string(&settings).unwrap(), json);
+    }
+
+    #[test]
+    fn thermostat_from_response() {
+        let thermostat: Thermostat = serde_json::from_str(
+            r#"{
+                "identifier": "522697894617",
+                "name": "Main Floor",
+                "thermostatRev": "220115210012",
+                "isRegistered": true,
+                "equipmentStatus": "heatPump,fan",
+                "settings": {"hvacMode": "auxHeatOnly", "vent": "minontime", "humidity": "36"},
+                "runtime": {
+                    "connected": true,
+                    "actualTemperature": 695,
+                    "actualHumidity": 41,
+                    "desiredHeatRange": [450, 790]
+                },
+                "program": {
+                    "schedule": [["home", "away"]],
+                    "climates": [{
+                        "name": "Home",
+                        "climateRef": "home",
+                        "type": "program",
+                        "sensors": [{"id": "rs:100:1", "name": "Bedroom"}]
+                    }],
+                    "currentClimateRef": "home"
+                },
+                "remoteSensors": [{
+                    "id": "rs:100",
+                    "name": "Bedroom",
+                    "type": "ecobee3_remote_sensor",
+                    "inUse": true,
+                    "capability": [{"id": "1", "type": "temperature", "value": "702"}]
+                }],
+                "oemCfg": {"undocumented": [1, 2]},
+                "somethingNew": {"ignored": true}
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(thermostat.identifier.as_str(), "522697894617");
+        let settings = thermostat.settings.unwrap();
+        assert_eq!(settings.hvacMode, Some(HvacMode::auxHeatOnly));
+        assert_eq!(settings.vent, Some(Vent::minontime));
+        assert_eq!(settings.humidifierMode, None);
+        let runtime = thermostat.runtime.unwrap();
+        assert_eq!(runtime.actualTemperature, Some(695));
+        assert_eq!(runtime.desiredHeatRange, Some(vec![450, 790]));
+        let program = thermostat.program.unwrap();
+        let climate = &program.climates.unwrap()[0];
+        assert_eq!(climate.r#type.as_deref(), Some("program"));
+        assert_eq!(
+            climate.sensors.as_ref().unwrap()[0].id.as_deref(),
+            Some("rs:100:1")
+        );
+        let sensor = &thermostat.remoteSensors.unwrap()[0];
+        assert_eq!(sensor.r#type, Some(RemoteSensorType::ecobee3_remote_sensor));
+        assert_eq!(
+            sensor.capability.as_ref().unwrap()[0].value.as_deref(),
+            Some("702")
+        );
+        assert!(thermostat.oemCfg.unwrap().0.contains_key("undocumented"));
+    }
+
+    #[test]
+    fn sensor_readings() {
+        let sensor: RemoteSensor = serde_json::from_str(
+            r#"{
+                "id": "rs:101",
+                "name": "Back Door",
+                "type": "some_future_sensor",
+                "capability": [
+                    {"id": "1", "type": "temperature", "value": "unknown"},
+                    {"id": "2", "type": "humidity", "value": "44"},
+                    {"id": "3", "type": "occupancy", "value": "true"},
+                    {"id": "4", "type": "dryContact", "value": "0"},
+                    {"id": "5", "type": "airQuality", "value": "12"}
+                ]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            sensor.r#type,
+            Some(RemoteSensorType::unknown("some_future_sensor".to_string()))
+        );
+        assert_eq!(
+            serde_json::to_value(&sensor.r#type).unwrap(),
+            "some_future_sensor"
+        );
+        assert_eq!(
+            sensor.readings().collect::<Vec<_>>(),
+            vec![
+                SensorReading::humidity(44),
+                SensorReading::occupancy(true),
+                SensorReading::dryContact(false),
+            ]
+        );
+    }
+}
@@ -0,0 +1,156 @@
+use crate::{EcobeeError, Scope, Secret};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Everything needed to rebuild a client after a restart.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StoredTokens {
+    pub access_token: Secret,
+    pub refresh_token: Secret,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<Scope>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub issued_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// Somewhere to keep tokens between runs.
+///
+/// ecobee rotates the refresh token every time it is used, so [`crate::Ecobee`] saves the new
+/// pair after every successful refresh.  Losing that write means being locked out after a restart.
+pub trait TokenStore: Send {
+    /// The last saved tokens, or `None` if nothing has been saved yet.
+    fn load(&self) -> Result<Option<StoredTokens>, EcobeeError>;
+    fn save(&self, tokens: &StoredTokens) -> Result<(), EcobeeError>;
+}
+
+/// Keeps tokens in a JSON file, replacing it atomically so a crash mid-write never leaves a
+/// truncated file behind.
+#[derive(Debug, Clone)]
+pub struct FileTokenStore {
+    path: PathBuf,
+}
+
+impl FileTokenStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        FileTokenStore { path: path.into() }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn temporary_path(&self) -> PathBuf {
+        let mut name = self.path.file_name().unwrap_or_default().to_os_string();
+        name.push(".tmp");
+        self.path.with_file_name(name)
+    }
+}
+
+impl TokenStore for FileTokenStore {
+    fn load(&self) -> Result<Option<StoredTokens>, EcobeeError> {
+        let contents = match fs::read_to_string(&self.path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(EcobeeError::TokenStore(e)),
+        };
+        // Deliberately not EcobeeError::Deserialize, which would carry the tokens in its body.
+        serde_json::from_str(&contents)
+            .map(Some)
+            .map_err(|e| EcobeeError::TokenStore(io::Error::new(io::ErrorKind::InvalidData, e)))
+    }
+
+    fn save(&self, tokens: &StoredTokens) -> Result<(), EcobeeError> {
+        let contents = serde_json::to_vec_pretty(tokens)
+            .map_err(|e| EcobeeError::TokenStore(io::Error::new(io::ErrorKind::InvalidData, e)))?;
+        let temporary = self.temporary_path();
+        let write = || -> io::Result<()> {
+            let mut options = fs::OpenOptions::new();
+            options.write(true).create(true).truncate(true);
+            #[cfg(unix)]
+            std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+            let mut file = options.open(&temporary)?;
+            file.write_all(&contents)?;
+            file.sync_all()?;
+            fs::rename(&temporary, &self.path)
+        };
+        write().map_err(|e| {
+            let _ = fs::remove_file(&temporary);
+            EcobeeError::TokenStore(e)
+        })
+    }
+}
+
+/// Keeps tokens for the life of the process.  Handy for tests and for services whose tokens are
+/// persisted by something else.
+#[derive(Debug, Default)]
+pub struct MemoryTokenStore {
+    tokens: Mutex<Option<StoredTokens>>,
+}
+
+impl MemoryTokenStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_tokens(tokens: StoredTokens) -> Self {
+        MemoryTokenStore {
+            tokens: Mutex::new(Some(tokens)),
+        }
+    }
+}
+
+impl TokenStore for MemoryTokenStore {
+    fn load(&self) -> Result<Option<StoredTokens>, EcobeeError> {
+        Ok(self
+            .tokens
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone())
+    }
+
+    fn save(&self, tokens: &StoredTokens) -> Result<(), EcobeeError> {
+        *self.tokens.lock().unwrap_or_else(|e| e.into_inner()) = Some(tokens.clone());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens() -> StoredTokens {
+        StoredTokens {
+            access_token: "access".into(),
+            refresh_token: "refresh".into(),
+            scope: Some(Scope::smartWrite),
+            issued_at: Some("2022-01-15T21:00:00Z".parse::<DateTime<Utc>>().unwrap()),
+            expires_at: Some("2022-01-15T22:00:00Z".parse::<DateTime<Utc>>().unwrap()),
+        }
+    }
+
+    #[test]
+    fn file_store_round_trip() {
+        let dir = std::env::temp_dir().join(format!("ecobee-token-store-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let store = FileTokenStore::new(dir.join("tokens.json"));
+        assert_eq!(store.load().unwrap(), None);
+        store.save(&tokens()).unwrap();
+        assert_eq!(store.load().unwrap(), Some(tokens()));
+        assert!(!store.temporary_path().exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn memory_store_round_trip() {
+        let store = MemoryTokenStore::new();
+        assert_eq!(store.load().unwrap(), None);
+        store.save(&tokens()).unwrap();
+        assert_eq!(store.load().unwrap(), Some(tokens()));
+    }
+}